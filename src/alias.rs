@@ -0,0 +1,89 @@
+//! Detection of alias pages.
+//!
+//! Many pages only point to the page of another command, e.g. the page of
+//! `tar` on some platforms just says "This command is an alias of `gnu-tar`."
+
+use crate::types::LineType;
+
+/// The phrase alias pages use to refer to the original command.
+const ALIAS_PHRASE: &str = "alias of `";
+
+/// A page that was shown instead of the alias page that was requested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alias {
+    /// The name of the requested alias page
+    pub name: String,
+    /// The name of the page that is shown instead
+    pub target: String,
+}
+
+/// Return the name of the page that a page is an alias of, or `None` if the
+/// page is not an alias page.
+///
+/// Only the description is considered, so pages that merely mention aliases
+/// in their examples are not treated as alias pages.
+pub fn alias_target(lines: impl IntoIterator<Item = LineType>) -> Option<String> {
+    lines
+        .into_iter()
+        .take_while(|line| {
+            matches!(
+                line,
+                LineType::Title(_) | LineType::Description(_) | LineType::Empty
+            )
+        })
+        .find_map(|line| match line {
+            LineType::Description(text) => target_of(&text),
+            _ => None,
+        })
+}
+
+/// Extract the page name from a description line like "This command is an
+/// alias of `git checkout`." Multi-word commands are joined with dashes, like
+/// the words of a command on the command line.
+fn target_of(description: &str) -> Option<String> {
+    let start = description.find(ALIAS_PHRASE)? + ALIAS_PHRASE.len();
+    let (command, _) = description[start..].split_once('`')?;
+    let target = command
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase();
+    (!target.is_empty()).then_some(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::line_iterator::LineIterator;
+
+    fn target(page: &str) -> Option<String> {
+        alias_target(LineIterator::new(page.as_bytes()))
+    }
+
+    #[test]
+    fn test_alias_target() {
+        let page = "# tar\n\n\
+                    > This command is an alias of `gnu-tar`.\n\n\
+                    - View documentation for the original command:\n\n\
+                    `tldr gnu-tar`\n";
+        assert_eq!(target(page).as_deref(), Some("gnu-tar"));
+
+        let page = "# git co\n\n\
+                    > This command is an alias of `git checkout`.\n\
+                    > More information: <https://example.com>.\n";
+        assert_eq!(target(page).as_deref(), Some("git-checkout"));
+    }
+
+    #[test]
+    fn test_alias_target_not_an_alias() {
+        assert_eq!(target("# tar\n\n> Archiving utility.\n"), None);
+        assert_eq!(target("# tar\n\n> An alias of ``.\n"), None);
+
+        // Mentions of aliases in the examples don't count
+        let page = "# git config\n\n\
+                    > Manage configuration.\n\n\
+                    - Create an alias of `git status`:\n\n\
+                    `git config alias.st status`\n";
+        assert_eq!(target(page), None);
+    }
+}
@@ -1,33 +1,65 @@
 use std::{
-    env,
+    cell::OnceCell,
+    collections::BTreeMap,
     ffi::OsStr,
     fs::{self, File},
     io::{BufReader, Cursor, Read},
     path::{Path, PathBuf},
-    time::{Duration, SystemTime},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use anyhow::{ensure, Context, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use log::debug;
-use reqwest::{blocking::Client, Proxy};
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use walkdir::{DirEntry, WalkDir};
 use zip::ZipArchive;
 
-use crate::{types::PlatformType, utils::print_warning};
+use crate::{
+    alias::Alias,
+    download::{Download, Downloader, HttpValidators},
+    extensions::Dedup,
+    index::{IndexEntry, PageIndex},
+    line_iterator::LineIterator,
+    types::{LineType, PlatformType},
+    utils::print_warning,
+};
 
 pub static TLDR_PAGES_DIR: &str = "tldr-pages";
 static TLDR_OLD_PAGES_DIR: &str = "tldr-master";
+/// Directory into which a new archive is extracted before it replaces the
+/// pages directory.
+static TLDR_STAGING_DIR: &str = "tldr-pages.staging";
+/// Directory in which the previous pages are kept while the new ones are
+/// being moved into place.
+static TLDR_BACKUP_DIR: &str = "tldr-pages.old";
+/// File name of the SHA-256 checksums of the archives, relative to the
+/// archive source.
+static CHECKSUMS_FILE_NAME: &str = "tldr.sha256sums";
+/// File in the cache directory that stores information about the last update.
+static METADATA_FILE_NAME: &str = "metadata.toml";
+/// The file containing the index of the pages.
+static INDEX_FILE_NAME: &str = "pages.index";
 
 #[derive(Debug)]
+#[allow(clippy::struct_field_names)]
 pub struct Cache {
     cache_dir: PathBuf,
     enable_styles: bool,
+    /// The page index, loaded (or rebuilt) on first use
+    index: OnceCell<Option<PageIndex>>,
 }
 
 #[derive(Debug)]
 pub struct PageLookupResult {
     pub page_path: PathBuf,
     pub patch_path: Option<PathBuf>,
+    /// The platform the page was found for, `None` for common and custom pages
+    pub platform: Option<PlatformType>,
+    /// Whether the page was found for a platform other than the requested ones
+    pub is_platform_fallback: bool,
+    /// The alias page that was requested, if this page is shown in its place
+    pub alias: Option<Alias>,
 }
 
 impl PageLookupResult {
@@ -35,6 +67,9 @@ impl PageLookupResult {
         Self {
             page_path,
             patch_path: None,
+            platform: None,
+            is_platform_fallback: false,
+            alias: None,
         }
     }
 
@@ -43,6 +78,17 @@ impl PageLookupResult {
         self
     }
 
+    pub fn with_platform(mut self, platform: PlatformType, is_fallback: bool) -> Self {
+        self.platform = Some(platform);
+        self.is_platform_fallback = is_fallback;
+        self
+    }
+
+    pub fn with_alias(mut self, alias: Alias) -> Self {
+        self.alias = Some(alias);
+        self
+    }
+
     /// Create a buffered reader that sequentially reads from the page and the
     /// patch, as if they were concatenated.
     ///
@@ -76,6 +122,72 @@ impl PageLookupResult {
     }
 }
 
+/// Information about the cached pages that is persisted across runs.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CacheMetadata {
+    /// Time of the last successful update, in seconds since the Unix epoch
+    pub updated_at: Option<u64>,
+    /// The archive source the pages were downloaded from
+    pub source: Option<String>,
+    /// The version of tealdeer that downloaded the pages
+    pub tealdeer_version: Option<String>,
+    /// The languages included in the cache
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// The downloaded archives, keyed by archive URL
+    #[serde(default)]
+    archives: BTreeMap<String, ArchiveMetadata>,
+}
+
+impl CacheMetadata {
+    /// Return the time of the last successful update.
+    pub fn updated_at(&self) -> Option<SystemTime> {
+        self.updated_at
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+    }
+}
+
+/// Information about a downloaded archive.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+struct ArchiveMetadata {
+    /// The SHA-256 checksum of the archive
+    sha256: String,
+    #[serde(flatten)]
+    validators: HttpValidators,
+}
+
+/// A summary of the cache contents, as reported by `--cache-info`.
+#[derive(Debug, Default)]
+pub struct CacheInfo {
+    /// The metadata stored by the last update, if any
+    pub metadata: Option<CacheMetadata>,
+    /// The number of pages, keyed by language and platform directory
+    pub page_counts: BTreeMap<String, BTreeMap<String, usize>>,
+    /// The total size of the files in the pages directory, in bytes
+    pub disk_usage: u64,
+    /// Whether the pages directory used by older tealdeer versions
+    /// (`tldr-master`) is still present
+    pub old_layout_present: bool,
+}
+
+/// A page as reported by `--list --long` and `--list --format json`.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct PageListEntry {
+    pub name: String,
+    /// The platform directories the page is available in, in the order of
+    /// the requested platforms
+    pub platforms: Vec<&'static str>,
+    /// Whether the page is a custom page
+    #[serde(rename = "custom")]
+    pub is_custom: bool,
+    /// Whether a custom patch is appended to the page
+    #[serde(rename = "patched")]
+    pub is_patched: bool,
+    /// The first line of the description, in the preferred language if
+    /// available
+    pub description: String,
+}
+
 pub enum CacheFreshness {
     /// The cache is still fresh (less than `MAX_CACHE_AGE` old)
     Fresh,
@@ -93,6 +205,7 @@ impl Cache {
         Self {
             cache_dir: cache_dir.into(),
             enable_styles,
+            index: OnceCell::new(),
         }
     }
 
@@ -135,70 +248,378 @@ impl Cache {
         self.cache_dir.join(TLDR_PAGES_DIR)
     }
 
-    /// Download the archive from the specified URL.
-    fn download(archive_url: &str) -> Result<Vec<u8>> {
-        let mut builder = Client::builder();
-        if let Ok(ref host) = env::var("HTTP_PROXY") {
-            if let Ok(proxy) = Proxy::http(host) {
-                builder = builder.proxy(proxy);
+    fn metadata_path(&self) -> PathBuf {
+        self.cache_dir.join(METADATA_FILE_NAME)
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.cache_dir.join(INDEX_FILE_NAME)
+    }
+
+    /// Return the index of the pages in the cache.
+    ///
+    /// The index is loaded once. If it is missing or does not match the
+    /// pages directory anymore, it is rebuilt and saved. Returns `None` if
+    /// there are no pages.
+    pub fn index(&self) -> Option<&PageIndex> {
+        self.index
+            .get_or_init(|| {
+                let pages_dir = self.pages_dir();
+                if !pages_dir.is_dir() {
+                    return None;
+                }
+                if let Some(index) = PageIndex::load(&self.index_path()) {
+                    if index.is_current(&pages_dir) {
+                        return Some(index);
+                    }
+                }
+                debug!("Rebuilding the page index");
+                self.build_index()
+                    .map_err(|e| debug!("Could not build the page index: {e:#}"))
+                    .ok()
+            })
+            .as_ref()
+    }
+
+    /// Return the index of the pages in the cache, but only if that doesn't
+    /// require rebuilding it, i.e. if it has already been loaded or the saved
+    /// index is current.
+    ///
+    /// Meant for single page lookups, which are cheaper to do on the
+    /// filesystem directly than rebuilding an index that cannot be saved
+    /// (e.g. in a read-only cache) on every invocation.
+    fn current_index(&self) -> Option<&PageIndex> {
+        if let Some(index) = self.index.get() {
+            return index.as_ref();
+        }
+        let index = PageIndex::load(&self.index_path())
+            .filter(|index| index.is_current(&self.pages_dir()))?;
+        self.index.get_or_init(|| Some(index)).as_ref()
+    }
+
+    /// Build the index of the pages in the cache and save it.
+    fn build_index(&self) -> Result<PageIndex> {
+        let index = PageIndex::build(&self.pages_dir())?;
+        // The index can still be used if it cannot be saved, it just has to
+        // be rebuilt next time
+        if let Err(e) = index.save(&self.index_path()) {
+            debug!("{e:#}");
+        }
+        Ok(index)
+    }
+
+    /// Return the index entry of a page, unless the page is a custom page or
+    /// has been patched.
+    pub fn indexed_page(&self, lookup_result: &PageLookupResult) -> Option<&IndexEntry> {
+        if lookup_result.patch_path.is_some() {
+            return None;
+        }
+        let path = lookup_result
+            .page_path
+            .strip_prefix(self.pages_dir())
+            .ok()?;
+        let mut components = path.iter().filter_map(OsStr::to_str);
+        let (Some(language_dir), Some(platform), Some(file_name)) =
+            (components.next(), components.next(), components.next())
+        else {
+            return None;
+        };
+        let name = file_name.strip_suffix(".md")?;
+        self.index()?.get(name, language_dir, platform)
+    }
+
+    /// Load the cache metadata. Returns `None` if the metadata is missing
+    /// (e.g. for caches created by older versions) or cannot be parsed.
+    pub fn metadata(&self) -> Option<CacheMetadata> {
+        let path = self.metadata_path();
+        let contents = fs::read_to_string(&path).ok()?;
+        toml::from_str(&contents)
+            .map_err(|e| debug!("Could not parse cache metadata at {}: {e}", path.display()))
+            .ok()
+    }
+
+    /// Persist the cache metadata.
+    fn save_metadata(&self, metadata: &CacheMetadata) -> Result<()> {
+        let path = self.metadata_path();
+        let serialized = toml::to_string(metadata).context("Failed to serialize cache metadata")?;
+        fs::write(&path, serialized)
+            .with_context(|| format!("Could not write cache metadata to {}", path.display()))
+    }
+
+    /// Update the pages cache from the first archive source that works.
+    ///
+    /// An archive source is the base URL under which both the per-language
+    /// pages archives and the checksums file are published. The sources are
+    /// tried in order, so mirrors can be listed after the preferred source.
+    ///
+    /// Only the archives of the given languages are downloaded. Languages for
+    /// which no translated pages exist are skipped. Unless `quiet` is set,
+    /// problems that don't stop the update are reported as warnings.
+    pub fn update(
+        &self,
+        downloader: &Downloader,
+        archive_sources: &[String],
+        languages: &[String],
+        quiet: bool,
+    ) -> Result<()> {
+        self.ensure_cache_dir_exists()?;
+
+        let mut errors = Vec::new();
+        for (i, archive_source) in archive_sources.iter().enumerate() {
+            match self.update_from_source(downloader, archive_source, languages, quiet) {
+                Ok(()) => return Ok(()),
+                Err(e) if archive_sources.len() == 1 => return Err(e),
+                Err(e) => {
+                    if !quiet && i + 1 < archive_sources.len() {
+                        print_warning(
+                            self.enable_styles,
+                            &format!(
+                                "Could not update from {archive_source}: {e:#}\n\
+                                 Trying the next archive source."
+                            ),
+                        );
+                    }
+                    errors.push(format!("- {archive_source}: {e:#}"));
+                }
             }
         }
-        if let Ok(ref host) = env::var("HTTPS_PROXY") {
-            if let Ok(proxy) = Proxy::https(host) {
-                builder = builder.proxy(proxy);
+
+        if errors.is_empty() {
+            bail!("No archive source configured");
+        }
+        Err(anyhow!(
+            "None of the archive sources could be used:\n{}",
+            errors.join("\n")
+        ))
+    }
+
+    /// Update the pages cache from the specified archive source.
+    fn update_from_source(
+        &self,
+        downloader: &Downloader,
+        archive_source: &str,
+        languages: &[String],
+        quiet: bool,
+    ) -> Result<()> {
+        // The checksums file lists every archive that is available, so it is
+        // needed first to determine which languages can be downloaded
+        let checksums = String::from_utf8(
+            downloader.download(&archive_file_url(archive_source, CHECKSUMS_FILE_NAME))?,
+        )
+        .context("The checksums file is not valid UTF-8")?;
+
+        let mut archives: Vec<(&str, String)> = Vec::new();
+        for language in languages {
+            let file_name = language_archive_file_name(language);
+            if archives.iter().any(|(_, name)| *name == file_name) {
+                continue;
+            }
+            if find_checksum(&checksums, &file_name).is_some() {
+                archives.push((language, file_name));
+            } else {
+                debug!("No pages available for language {}", language);
             }
         }
-        let client = builder
-            .build()
-            .context("Could not instantiate HTTP client")?;
-        let mut resp = client
-            .get(archive_url)
-            .send()?
-            .error_for_status()
-            .with_context(|| format!("Could not download tldr pages from {archive_url}"))?;
-        let mut buf: Vec<u8> = vec![];
-        let bytes_downloaded = resp.copy_to(&mut buf)?;
-        debug!("{} bytes downloaded", bytes_downloaded);
-        Ok(buf)
-    }
-
-    /// Update the pages cache from the specified URL.
-    pub fn update(&self, archive_url: &str) -> Result<()> {
-        self.ensure_cache_dir_exists()?;
+        ensure!(
+            !archives.is_empty(),
+            "No pages available for any of the languages {}",
+            languages.join(", ")
+        );
 
-        // First, download the compressed data
-        let bytes: Vec<u8> = Self::download(archive_url)?;
+        // Only ask the server whether the archives have changed if the pages
+        // from the previous download are still around and contain the same
+        // set of languages
+        let mut metadata = self.metadata().unwrap_or_default();
+        let urls: Vec<String> = archives
+            .iter()
+            .map(|(_, file_name)| archive_file_url(archive_source, file_name))
+            .collect();
+        let mut unchanged_so_far = self.pages_dir().is_dir()
+            && metadata.archives.len() == urls.len()
+            && urls.iter().all(|url| metadata.archives.contains_key(url));
 
-        // Decompress the response body into an `Archive`
-        let mut archive = ZipArchive::new(Cursor::new(bytes))
-            .context("Could not decompress downloaded ZIP archive")?;
+        // Download the compressed data. As soon as one of the archives has
+        // changed, the remaining ones are downloaded unconditionally, since
+        // the pages directory will be replaced as a whole.
+        let mut downloads: Vec<Option<(Vec<u8>, HttpValidators)>> = Vec::new();
+        for url in &urls {
+            let validators = metadata
+                .archives
+                .get(url)
+                .map(|archive| &archive.validators)
+                .filter(|_| unchanged_so_far);
+            match downloader.download_if_modified(url, validators)? {
+                Download::Modified(bytes, validators) => {
+                    unchanged_so_far = false;
+                    downloads.push(Some((bytes, validators)));
+                }
+                Download::NotModified => downloads.push(None),
+            }
+        }
+        if unchanged_so_far {
+            // Nothing changed, just remember that the cache is up to date
+            metadata.updated_at = Some(unix_timestamp_now());
+            return self.save_metadata(&metadata);
+        }
 
-        // Clear cache directory
-        // Note: This is not the best solution. Ideally we would download the
-        // archive to a temporary directory and then swap the two directories.
-        // But renaming a directory doesn't work across filesystems and Rust
-        // does not yet offer a recursive directory copying function. So for
-        // now, we'll use this approach.
-        self.clear()
-            .context("Could not clear the cache directory")?;
+        let mut new_archives = BTreeMap::new();
+        let mut language_archives = Vec::new();
+        for (((language, file_name), url), download) in archives.iter().zip(urls).zip(downloads) {
+            let (bytes, validators) = match download {
+                Some(download) => download,
+                None => downloader.download_with_validators(&url)?,
+            };
+
+            // Make sure the archive is what upstream published before touching
+            // the cache
+            let sha256 = verify_checksum(&bytes, file_name, &checksums)?;
 
-        // Extract archive into pages dir
-        archive
-            .extract(self.pages_dir())
-            .context("Could not unpack compressed data")?;
+            language_archives.push((language_dir(language), bytes));
+            new_archives.insert(url, ArchiveMetadata { sha256, validators });
+        }
+
+        // Then replace the current pages with the contents of the archives
+        self.install_archives(language_archives)?;
+        if let Err(e) = self.build_index() {
+            debug!("Could not build the page index: {e:#}");
+        }
+
+        // Remember where the pages came from. Not being able to do so only
+        // means that the next update will download the archives again, and
+        // that the modification time of the pages directory is used to
+        // determine the cache age.
+        let metadata = CacheMetadata {
+            updated_at: Some(unix_timestamp_now()),
+            source: Some(archive_source.to_string()),
+            tealdeer_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            languages: archives
+                .iter()
+                .map(|(language, _)| (*language).to_string())
+                .collect(),
+            archives: new_archives,
+        };
+        if let Err(e) = self.save_metadata(&metadata) {
+            if !quiet {
+                print_warning(self.enable_styles, &format!("{e:#}"));
+            }
+        }
 
         Ok(())
     }
 
-    /// Return the duration since the cache directory was last modified.
+    /// Extract the archives into the given language directories of a staging
+    /// directory and, if that succeeds, swap it into place.
+    ///
+    /// The staging directory lives inside the cache directory, so it is on the
+    /// same filesystem as the pages directory and can be moved with a simple
+    /// rename. The current pages are not touched until the new ones have been
+    /// extracted and validated, which means that a failed or interrupted
+    /// update leaves the existing cache intact.
+    fn install_archives(&self, language_archives: Vec<(String, Vec<u8>)>) -> Result<()> {
+        // Decompress the response bodies into `Archive`s
+        let mut archives = language_archives
+            .into_iter()
+            .map(|(language_dir, bytes)| {
+                ZipArchive::new(Cursor::new(bytes))
+                    .map(|archive| (language_dir, archive))
+                    .context("Could not decompress downloaded ZIP archive")
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Restore the pages if a previous update was interrupted while
+        // swapping directories, and remove leftovers of aborted extractions.
+        self.recover_interrupted_update()?;
+        let staging_dir = self.cache_dir.join(TLDR_STAGING_DIR);
+        remove_dir_if_exists(&staging_dir)?;
+
+        // Extract archives into staging dir
+        let extracted = archives
+            .iter_mut()
+            .try_for_each(|(language_dir, archive)| {
+                archive
+                    .extract(staging_dir.join(language_dir))
+                    .context("Could not unpack compressed data")
+            })
+            .and_then(|()| validate_pages_dir(&staging_dir));
+        if let Err(e) = extracted {
+            if let Err(cleanup_error) = fs::remove_dir_all(&staging_dir) {
+                debug!("Could not remove staging directory: {cleanup_error}");
+            }
+            return Err(e);
+        }
+
+        self.swap_pages_dir(&staging_dir)
+    }
+
+    /// Replace the pages directory with `new_pages_dir`.
+    ///
+    /// Renaming a directory onto an existing one is not possible on all
+    /// platforms, so the old pages are moved out of the way first and only
+    /// deleted once the new pages are in place.
+    fn swap_pages_dir(&self, new_pages_dir: &Path) -> Result<()> {
+        let pages_dir = self.pages_dir();
+        let backup_dir = self.cache_dir.join(TLDR_BACKUP_DIR);
+
+        remove_dir_if_exists(&backup_dir)?;
+        if pages_dir.exists() {
+            fs::rename(&pages_dir, &backup_dir).with_context(|| {
+                format!(
+                    "Could not move the old pages out of the way to {}",
+                    backup_dir.display()
+                )
+            })?;
+        }
+
+        if let Err(e) = fs::rename(new_pages_dir, &pages_dir) {
+            // Put the old pages back, so we're not left without a cache
+            if backup_dir.exists() {
+                if let Err(restore_error) = fs::rename(&backup_dir, &pages_dir) {
+                    debug!("Could not restore the old pages: {restore_error}");
+                }
+            }
+            return Err(e).with_context(|| {
+                format!("Could not move the new pages to {}", pages_dir.display())
+            });
+        }
+
+        // The new pages are in place, the old ones are no longer needed.
+        // Delete old tldr-pages cache location as well if present
+        // TODO: To be removed in the future
+        remove_dir_if_exists(&backup_dir)?;
+        remove_dir_if_exists(&self.cache_dir.join(TLDR_OLD_PAGES_DIR))?;
+
+        Ok(())
+    }
+
+    /// If an update was interrupted after moving the old pages out of the way
+    /// but before moving the new pages into place, move the old pages back.
+    fn recover_interrupted_update(&self) -> Result<()> {
+        let pages_dir = self.pages_dir();
+        let backup_dir = self.cache_dir.join(TLDR_BACKUP_DIR);
+        if !pages_dir.exists() && backup_dir.is_dir() {
+            debug!("Restoring pages from {}", backup_dir.display());
+            fs::rename(&backup_dir, &pages_dir).with_context(|| {
+                format!(
+                    "Could not restore the pages from {} after an interrupted update",
+                    backup_dir.display()
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Return the duration since the last cache update.
+    ///
+    /// The update time is read from the cache metadata. Caches created by
+    /// older versions of tealdeer don't have any metadata, for those the
+    /// modification time of the pages directory is used instead.
     pub fn last_update(&self) -> Option<Duration> {
-        if let Ok(metadata) = fs::metadata(self.pages_dir()) {
-            if let Ok(mtime) = metadata.modified() {
-                let now = SystemTime::now();
-                return now.duration_since(mtime).ok();
-            };
-        };
-        None
+        let pages_dir_metadata = fs::metadata(self.pages_dir()).ok()?;
+        let updated_at = self
+            .metadata()
+            .and_then(|metadata| metadata.updated_at())
+            .or_else(|| pages_dir_metadata.modified().ok())?;
+        SystemTime::now().duration_since(updated_at).ok()
     }
 
     /// Return the freshness of the cache (fresh, stale or missing).
@@ -225,16 +646,32 @@ impl Cache {
     }
 
     /// Check for pages for a given platform in one of the given languages.
+    ///
+    /// Without an index (e.g. because it is outdated or could not be built),
+    /// the pages directory is checked directly.
     fn find_page_for_platform(
+        index: Option<&PageIndex>,
         page_name: &str,
         pages_dir: &Path,
         platform: &str,
         language_dirs: &[String],
     ) -> Option<PathBuf> {
-        language_dirs
-            .iter()
-            .map(|lang_dir| pages_dir.join(lang_dir).join(platform).join(page_name))
-            .find(|path| path.exists() && path.is_file())
+        let page_path = |lang_dir: &String| {
+            pages_dir
+                .join(lang_dir)
+                .join(platform)
+                .join(format!("{page_name}.md"))
+        };
+        match index {
+            Some(index) => language_dirs
+                .iter()
+                .find(|lang_dir| index.get(page_name, lang_dir, platform).is_some())
+                .map(page_path),
+            None => language_dirs
+                .iter()
+                .map(page_path)
+                .find(|path| path.exists() && path.is_file()),
+        }
     }
 
     /// Look up custom patch (<name>.patch.md). If it exists, store it in a variable.
@@ -252,28 +689,32 @@ impl Cache {
         custom_pages_dir: Option<&Path>,
         platforms: &[PlatformType],
     ) -> Option<PageLookupResult> {
-        let page_filename = format!("{name}.md");
+        if let Some(config_dir) = custom_pages_dir {
+            // TODO: Remove this check 1 year after version 1.7.0 was released
+            self.check_for_old_custom_pages(config_dir);
+        }
+        self.lookup_page(name, languages, custom_pages_dir, platforms)
+    }
+
+    /// Like [`Cache::find_page`], but without checking the custom pages
+    /// directory for pages using the old naming convention. Meant for looking
+    /// up many pages at once.
+    pub fn lookup_page(
+        &self,
+        name: &str,
+        languages: &[String],
+        custom_pages_dir: Option<&Path>,
+        platforms: &[PlatformType],
+    ) -> Option<PageLookupResult> {
         let patch_filename = format!("{name}.patch.md");
         let custom_filename = format!("{name}.page.md");
 
         // Determine directory paths
         let pages_dir = self.pages_dir();
-        let lang_dirs: Vec<String> = languages
-            .iter()
-            .map(|lang| {
-                if lang == "en" {
-                    String::from("pages")
-                } else {
-                    format!("pages.{lang}")
-                }
-            })
-            .collect();
+        let lang_dirs: Vec<String> = languages.iter().map(|lang| language_dir(lang)).collect();
 
         // Look up custom page (<name>.page.md). If it exists, return it directly
         if let Some(config_dir) = custom_pages_dir {
-            // TODO: Remove this check 1 year after version 1.7.0 was released
-            self.check_for_old_custom_pages(config_dir);
-
             let custom_page = config_dir.join(custom_filename);
             if custom_page.exists() && custom_page.is_file() {
                 return Some(PageLookupResult::with_page(custom_page));
@@ -281,20 +722,44 @@ impl Cache {
         }
 
         let patch_path = Self::find_patch(&patch_filename, custom_pages_dir);
+        let index = self.current_index();
 
         // Try to find a platform specific path next, in the order supplied by the user, and append custom patch to it.
         for &platform in platforms {
             let platform_dir = Cache::get_platform_dir(platform);
             if let Some(page) =
-                Self::find_page_for_platform(&page_filename, &pages_dir, platform_dir, &lang_dirs)
+                Self::find_page_for_platform(index, name, &pages_dir, platform_dir, &lang_dirs)
             {
-                return Some(PageLookupResult::with_page(page).with_optional_patch(patch_path));
+                return Some(
+                    PageLookupResult::with_page(page)
+                        .with_optional_patch(patch_path)
+                        .with_platform(platform, false),
+                );
             }
         }
 
         // Did not find platform specific results, fall back to "common"
-        Self::find_page_for_platform(&page_filename, &pages_dir, "common", &lang_dirs)
-            .map(|page| PageLookupResult::with_page(page).with_optional_patch(patch_path))
+        if let Some(page) =
+            Self::find_page_for_platform(index, name, &pages_dir, "common", &lang_dirs)
+        {
+            return Some(PageLookupResult::with_page(page).with_optional_patch(patch_path));
+        }
+
+        // As a last resort, look for the page in the directories of all other
+        // platforms, as recommended by the client specification
+        PlatformType::all()
+            .iter()
+            .filter(|platform| !platforms.contains(platform))
+            .find_map(|&platform| {
+                let platform_dir = Cache::get_platform_dir(platform);
+                Self::find_page_for_platform(index, name, &pages_dir, platform_dir, &lang_dirs).map(
+                    |page| {
+                        PageLookupResult::with_page(page)
+                            .with_optional_patch(patch_path.clone())
+                            .with_platform(platform, true)
+                    },
+                )
+            })
     }
 
     /// Return the available pages.
@@ -303,13 +768,59 @@ impl Cache {
         custom_pages_dir: Option<&Path>,
         platforms: &[PlatformType],
     ) -> Vec<String> {
-        // Determine platforms directory and platform
-        let platforms_dir = self.pages_dir().join("pages");
-        let platform_dirs: Vec<&'static str> = platforms
+        let mut platform_dirs: Vec<&'static str> = platforms
             .iter()
             .map(|&p| Self::get_platform_dir(p))
             .collect();
+        platform_dirs.push("common");
+
+        let to_stem_custom = |entry: DirEntry| -> Option<String> {
+            entry
+                .path()
+                .file_name()
+                .and_then(OsStr::to_str)
+                .and_then(|s| s.strip_suffix(".page.md"))
+                .map(str::to_string)
+        };
+
+        // Common and (if applicable) platform specific pages
+        let mut pages: Vec<String> = match self.index() {
+            Some(index) => index
+                .names("pages", &platform_dirs)
+                .map(str::to_string)
+                .collect(),
+            None => self.walk_pages(&platform_dirs),
+        };
+
+        if let Some(custom_pages_dir) = custom_pages_dir {
+            let is_page = |entry: &DirEntry| -> bool {
+                entry.file_type().is_file()
+                    && entry
+                        .path()
+                        .file_name()
+                        .and_then(OsStr::to_str)
+                        .is_some_and(|file_name| file_name.ends_with(".page.md"))
+            };
+
+            let custom_pages = WalkDir::new(custom_pages_dir)
+                .min_depth(1)
+                .max_depth(1)
+                .into_iter()
+                .filter_entry(is_page)
+                .filter_map(Result::ok)
+                .filter_map(to_stem_custom);
+
+            pages.extend(custom_pages);
+        }
+
+        pages.sort();
+        pages.dedup();
+        pages
+    }
 
+    /// List the English pages in the given platform directories by walking
+    /// the pages directory, for when there is no index.
+    fn walk_pages(&self, platform_dirs: &[&str]) -> Vec<String> {
         // Closure that allows the WalkDir instance to traverse platform
         // specific and common page directories, but not others.
         let should_walk = |entry: &DirEntry| -> bool {
@@ -318,7 +829,7 @@ impl Cache {
                 return false;
             };
             if file_type.is_dir() {
-                return file_name == "common" || platform_dirs.contains(&file_name);
+                return platform_dirs.contains(&file_name);
             } else if file_type.is_file() {
                 return true;
             }
@@ -333,55 +844,152 @@ impl Cache {
                 .map(str::to_string)
         };
 
-        let to_stem_custom = |entry: DirEntry| -> Option<String> {
-            entry
-                .path()
-                .file_name()
-                .and_then(OsStr::to_str)
-                .and_then(|s| s.strip_suffix(".page.md"))
-                .map(str::to_string)
-        };
-
         // Recursively walk through common and (if applicable) platform specific directory
-        let mut pages = WalkDir::new(platforms_dir)
+        WalkDir::new(self.pages_dir().join("pages"))
             .min_depth(1) // Skip root directory
             .into_iter()
             .filter_entry(should_walk) // Filter out pages for other architectures
             .filter_map(Result::ok) // Convert results to options, filter out errors
             .filter_map(|e| {
+                // Pages are always located in a platform directory, other files
+                // (e.g. a license file next to the platform directories) are
+                // not pages
                 let extension = e.path().extension().unwrap_or_default();
-                if e.file_type().is_file() && extension == "md" {
+                if e.file_type().is_file() && e.depth() == 2 && extension == "md" {
                     to_stem(e)
                 } else {
                     None
                 }
             })
-            .collect::<Vec<String>>();
+            .collect()
+    }
 
-        if let Some(custom_pages_dir) = custom_pages_dir {
-            let is_page = |entry: &DirEntry| -> bool {
-                entry.file_type().is_file()
-                    && entry
-                        .path()
-                        .file_name()
-                        .and_then(OsStr::to_str)
-                        .map_or(false, |file_name| file_name.ends_with(".page.md"))
-            };
+    /// Return the available pages along with their platforms, custom page
+    /// status and description.
+    pub fn list_page_details(
+        &self,
+        languages: &[String],
+        custom_pages_dir: Option<&Path>,
+        platforms: &[PlatformType],
+    ) -> Vec<PageListEntry> {
+        let mut platform_dirs: Vec<&'static str> = platforms
+            .iter()
+            .map(|&p| Self::get_platform_dir(p))
+            .collect();
+        platform_dirs.push("common");
+        platform_dirs.clear_duplicates();
+        let mut lang_dirs: Vec<String> = languages.iter().map(|lang| language_dir(lang)).collect();
+        lang_dirs.push(language_dir("en"));
+        let pages_dir = self.pages_dir();
+        let index = self.index();
 
-            let custom_pages = WalkDir::new(custom_pages_dir)
-                .min_depth(1)
-                .max_depth(1)
-                .into_iter()
-                .filter_entry(is_page)
-                .filter_map(Result::ok)
-                .filter_map(to_stem_custom);
+        self.list_pages(custom_pages_dir, platforms)
+            .into_iter()
+            .map(|name| {
+                let custom_file = |suffix: &str| {
+                    custom_pages_dir
+                        .map(|dir| dir.join(format!("{name}.{suffix}")))
+                        .filter(|path| path.is_file())
+                };
+                let custom_page = custom_file("page.md");
+                let is_patched = custom_page.is_none() && custom_file("patch.md").is_some();
 
-            pages.extend(custom_pages);
+                // Without an index, the pages directory is checked directly
+                let entries = index.map_or(&[][..], |index| index.entries(&name));
+                let page_file = |lang_dir: &str, dir: &str| {
+                    pages_dir
+                        .join(lang_dir)
+                        .join(dir)
+                        .join(format!("{name}.md"))
+                };
+                let page_platforms = platform_dirs
+                    .iter()
+                    .copied()
+                    .filter(|&dir| match index {
+                        Some(_) => entries.iter().any(|entry| entry.platform == dir),
+                        None => lang_dirs
+                            .iter()
+                            .any(|lang_dir| page_file(lang_dir, dir).is_file()),
+                    })
+                    .collect();
+
+                let description = if let Some(path) = &custom_page {
+                    first_description(path).unwrap_or_default()
+                } else {
+                    lang_dirs
+                        .iter()
+                        .flat_map(|lang_dir| platform_dirs.iter().map(move |&dir| (lang_dir, dir)))
+                        .find_map(|(lang_dir, dir)| {
+                            if index.is_some() {
+                                entries
+                                    .iter()
+                                    .find(|entry| {
+                                        entry.language_dir == *lang_dir && entry.platform == dir
+                                    })
+                                    .map(|entry| entry.summary.clone())
+                            } else {
+                                let path = page_file(lang_dir, dir);
+                                path.is_file()
+                                    .then(|| first_description(&path).unwrap_or_default())
+                            }
+                        })
+                        .unwrap_or_default()
+                };
+
+                PageListEntry {
+                    is_custom: custom_page.is_some(),
+                    is_patched,
+                    platforms: page_platforms,
+                    description,
+                    name,
+                }
+            })
+            .collect()
+    }
+
+    /// Collect information about the cache contents.
+    ///
+    /// Unlike [`Cache::list_pages`], this walks the pages of all languages and
+    /// platforms.
+    pub fn info(&self) -> CacheInfo {
+        let mut info = CacheInfo {
+            metadata: self.metadata(),
+            old_layout_present: self.cache_dir.join(TLDR_OLD_PAGES_DIR).is_dir(),
+            ..CacheInfo::default()
+        };
+
+        // The pages are located at `<language dir>/<platform>/<page>.md`
+        for entry in WalkDir::new(self.pages_dir())
+            .min_depth(1)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+        {
+            info.disk_usage += entry.metadata().map_or(0, |md| md.len());
+
+            let extension = entry.path().extension().unwrap_or_default();
+            if entry.depth() != 3 || extension != "md" {
+                continue;
+            }
+            let mut dirs = entry
+                .path()
+                .ancestors()
+                .skip(1)
+                .filter_map(|dir| dir.file_name().and_then(OsStr::to_str));
+            let (Some(platform), Some(language)) =
+                (dirs.next(), dirs.next().and_then(language_of_dir))
+            else {
+                continue;
+            };
+            *info
+                .page_counts
+                .entry(language.to_string())
+                .or_default()
+                .entry(platform.to_string())
+                .or_default() += 1;
         }
 
-        pages.sort();
-        pages.dedup();
-        pages
+        info
     }
 
     /// Delete the cache directory
@@ -399,17 +1007,21 @@ impl Cache {
         );
 
         // Delete old tldr-pages cache location as well if present
-        // TODO: To be removed in the future
-        for pages_dir_name in [TLDR_PAGES_DIR, TLDR_OLD_PAGES_DIR] {
-            let pages_dir = self.cache_dir.join(pages_dir_name);
-
-            if pages_dir.exists() {
-                fs::remove_dir_all(&pages_dir).with_context(|| {
-                    format!(
-                        "Could not remove the cache directory at {}",
-                        pages_dir.display()
-                    )
-                })?;
+        // (TODO: To be removed in the future), along with any leftovers of an
+        // interrupted update
+        for pages_dir_name in [
+            TLDR_PAGES_DIR,
+            TLDR_OLD_PAGES_DIR,
+            TLDR_STAGING_DIR,
+            TLDR_BACKUP_DIR,
+        ] {
+            remove_dir_if_exists(&self.cache_dir.join(pages_dir_name))?;
+        }
+
+        for file in [self.metadata_path(), self.index_path()] {
+            if file.exists() {
+                fs::remove_file(&file)
+                    .with_context(|| format!("Could not remove {}", file.display()))?;
             }
         }
 
@@ -450,6 +1062,114 @@ impl Cache {
     }
 }
 
+/// Return the URL of a file published under the given archive source.
+fn archive_file_url(archive_source: &str, file_name: &str) -> String {
+    format!("{}/{file_name}", archive_source.trim_end_matches('/'))
+}
+
+/// Return the name of the directory containing the pages in `language`.
+fn language_dir(language: &str) -> String {
+    if language == "en" {
+        String::from("pages")
+    } else {
+        format!("pages.{language}")
+    }
+}
+
+/// Return the first line of the description of a page.
+fn first_description(path: &Path) -> Option<String> {
+    let file = File::open(path)
+        .map_err(|e| debug!("Could not open {}: {e}", path.display()))
+        .ok()?;
+    LineIterator::new(BufReader::new(file)).find_map(|line| match line {
+        LineType::Description(text) => Some(text),
+        _ => None,
+    })
+}
+
+/// Return the language of a pages directory, the inverse of [`language_dir`].
+fn language_of_dir(dir_name: &str) -> Option<&str> {
+    if dir_name == "pages" {
+        Some("en")
+    } else {
+        dir_name.strip_prefix("pages.")
+    }
+}
+
+/// Return the file name of the pages archive for `language`.
+fn language_archive_file_name(language: &str) -> String {
+    format!("tldr-pages.{language}.zip")
+}
+
+/// Return the number of seconds since the Unix epoch.
+fn unix_timestamp_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Look up the expected checksum of `file_name` in a checksums file.
+///
+/// The file is expected to be in the format produced by `sha256sum`, i.e. one
+/// `<hex digest>  <file name>` entry per line. The file name may be prefixed
+/// with `*` to indicate binary mode.
+fn find_checksum<'a>(checksums: &'a str, file_name: &str) -> Option<&'a str> {
+    checksums.lines().find_map(|line| {
+        let (checksum, name) = line.trim().split_once(char::is_whitespace)?;
+        let name = name.trim_start();
+        (name.strip_prefix('*').unwrap_or(name) == file_name).then_some(checksum)
+    })
+}
+
+/// Make sure that the SHA-256 checksum of `bytes` matches the one listed for
+/// `file_name` in `checksums`, and return it.
+fn verify_checksum(bytes: &[u8], file_name: &str, checksums: &str) -> Result<String> {
+    let expected = find_checksum(checksums, file_name)
+        .with_context(|| format!("No checksum found for {file_name}"))?;
+    let actual = format!("{:x}", Sha256::digest(bytes));
+    ensure!(
+        actual.eq_ignore_ascii_case(expected),
+        "Checksum mismatch for {file_name}: expected {expected}, got {actual}. \
+         The downloaded archive may be corrupted or tampered with, refusing to update the cache."
+    );
+    debug!("Checksum of {} verified", file_name);
+    Ok(actual)
+}
+
+/// Recursively delete a directory, if it exists.
+fn remove_dir_if_exists(dir: &Path) -> Result<()> {
+    if dir.exists() {
+        fs::remove_dir_all(dir).with_context(|| {
+            format!("Could not remove the cache directory at {}", dir.display())
+        })?;
+    }
+    Ok(())
+}
+
+/// Make sure that an extracted archive actually contains pages before it is
+/// used to replace the current cache.
+fn validate_pages_dir(pages_dir: &Path) -> Result<()> {
+    let contains_pages = fs::read_dir(pages_dir)
+        .with_context(|| format!("Could not read directory {}", pages_dir.display()))?
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name == "pages" || name.starts_with("pages."))
+        })
+        .any(|entry| {
+            fs::read_dir(entry.path()).is_ok_and(|mut platforms| {
+                platforms.any(|platform| platform.is_ok_and(|p| p.path().is_dir()))
+            })
+        });
+    ensure!(
+        contains_pages,
+        "The downloaded archive does not contain any pages"
+    );
+    Ok(())
+}
+
 /// Unit Tests for cache module
 #[cfg(test)]
 mod tests {
@@ -504,4 +1224,278 @@ mod tests {
 
         assert_eq!(&buf, b"Hello\n");
     }
+
+    /// Create a ZIP archive containing the given files.
+    fn make_archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_install_archive_replaces_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path(), false);
+
+        cache
+            .install_archives(vec![
+                ("pages".into(), make_archive(&[("common/old.md", "# old")])),
+                (
+                    "pages.de".into(),
+                    make_archive(&[("common/alt.md", "# alt")]),
+                ),
+            ])
+            .unwrap();
+        cache
+            .install_archives(vec![(
+                "pages".into(),
+                make_archive(&[("common/new.md", "# new")]),
+            )])
+            .unwrap();
+
+        let common_dir = cache.pages_dir().join("pages").join("common");
+        assert!(common_dir.join("new.md").is_file());
+        assert!(!common_dir.join("old.md").exists());
+        assert!(!cache.pages_dir().join("pages.de").exists());
+        assert!(!dir.path().join(TLDR_STAGING_DIR).exists());
+        assert!(!dir.path().join(TLDR_BACKUP_DIR).exists());
+    }
+
+    #[test]
+    fn test_install_invalid_archive_keeps_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path(), false);
+
+        cache
+            .install_archives(vec![(
+                "pages".into(),
+                make_archive(&[("common/old.md", "# old")]),
+            )])
+            .unwrap();
+
+        // Neither garbage nor an archive without pages may replace the cache
+        assert!(cache
+            .install_archives(vec![("pages".into(), b"garbage".to_vec())])
+            .is_err());
+        assert!(cache
+            .install_archives(vec![(
+                "pages".into(),
+                make_archive(&[("README.md", "# readme")])
+            )])
+            .is_err());
+
+        let common_dir = cache.pages_dir().join("pages").join("common");
+        assert!(common_dir.join("old.md").is_file());
+        assert!(!dir.path().join(TLDR_STAGING_DIR).exists());
+    }
+
+    #[test]
+    fn test_find_checksum() {
+        let checksums = "\
+            0123abcd  tldr.zip\n\
+            4567ef01 *tldr-pages.de.zip\n\
+            \n";
+        assert_eq!(find_checksum(checksums, "tldr.zip"), Some("0123abcd"));
+        assert_eq!(
+            find_checksum(checksums, "tldr-pages.de.zip"),
+            Some("4567ef01")
+        );
+        assert_eq!(find_checksum(checksums, "tldr-pages.fr.zip"), None);
+        assert_eq!(find_checksum(checksums, "zip"), None);
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        // SHA-256 of "hello"
+        let checksums =
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  tldr.zip\n";
+        assert!(verify_checksum(b"hello", "tldr.zip", checksums).is_ok());
+        // The digest is compared case-insensitively
+        let uppercase_digest =
+            "2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824  tldr.zip\n";
+        assert!(verify_checksum(b"hello", "tldr.zip", uppercase_digest).is_ok());
+
+        let error = verify_checksum(b"hellO", "tldr.zip", checksums).unwrap_err();
+        assert!(error.to_string().contains("Checksum mismatch for tldr.zip"));
+
+        let error = verify_checksum(b"hello", "other.zip", checksums).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("No checksum found for other.zip"));
+        // File names are matched exactly
+        let error = verify_checksum(b"hello", "tldr.zip", &checksums.to_uppercase()).unwrap_err();
+        assert!(error.to_string().contains("No checksum found for tldr.zip"));
+    }
+
+    #[test]
+    fn test_metadata_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path(), false);
+
+        assert_eq!(cache.metadata(), None);
+
+        let metadata = CacheMetadata {
+            updated_at: Some(1_700_000_000),
+            source: Some("https://example.com/".to_string()),
+            tealdeer_version: Some("1.2.3".to_string()),
+            languages: vec!["en".to_string(), "de".to_string()],
+            archives: BTreeMap::from([(
+                "https://example.com/tldr-pages.en.zip".to_string(),
+                ArchiveMetadata {
+                    sha256: "0123abcd".to_string(),
+                    validators: HttpValidators {
+                        etag: Some("\"abc\"".to_string()),
+                        last_modified: None,
+                    },
+                },
+            )]),
+        };
+        cache.save_metadata(&metadata).unwrap();
+        assert_eq!(cache.metadata(), Some(metadata));
+
+        // Clearing the cache removes the metadata as well
+        cache.clear().unwrap();
+        assert!(!cache.metadata_path().exists());
+    }
+
+    #[test]
+    fn test_last_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path(), false);
+        assert_eq!(cache.last_update(), None);
+
+        // Without metadata, the modification time of the pages dir is used
+        fs::create_dir(cache.pages_dir()).unwrap();
+        filetime::set_file_mtime(cache.pages_dir(), filetime::FileTime::from_unix_time(1, 0))
+            .unwrap();
+        assert!(cache.last_update().unwrap() > Duration::from_secs(3600));
+
+        // With metadata, the modification time is irrelevant
+        cache
+            .save_metadata(&CacheMetadata {
+                updated_at: Some(unix_timestamp_now()),
+                ..CacheMetadata::default()
+            })
+            .unwrap();
+        assert!(cache.last_update().unwrap() < Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_info() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path(), false);
+        let info = cache.info();
+        assert!(info.metadata.is_none());
+        assert!(info.page_counts.is_empty());
+        assert_eq!(info.disk_usage, 0);
+        assert!(!info.old_layout_present);
+
+        let pages_dir = cache.pages_dir();
+        for (path, contents) in [
+            ("pages/common/tar.md", "# tar"),
+            ("pages/common/ls.md", "# ls"),
+            ("pages/linux/ip.md", "# ip"),
+            ("pages/LICENSE.md", "MIT"),
+            ("pages.de/common/tar.md", "# tar"),
+        ] {
+            let path = pages_dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        fs::create_dir(dir.path().join(TLDR_OLD_PAGES_DIR)).unwrap();
+
+        let info = cache.info();
+        let counts = |language: &str| {
+            info.page_counts[language]
+                .iter()
+                .map(|(platform, &count)| (platform.as_str(), count))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(counts("en"), [("common", 2), ("linux", 1)]);
+        assert_eq!(counts("de"), [("common", 1)]);
+        assert_eq!(info.disk_usage, 5 + 4 + 4 + 3 + 5);
+        assert!(info.old_layout_present);
+    }
+
+    #[test]
+    fn test_recover_interrupted_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path(), false);
+
+        // Simulate an update that was interrupted right after moving the old
+        // pages out of the way
+        let backup_common_dir = dir.path().join(TLDR_BACKUP_DIR).join("pages/common");
+        fs::create_dir_all(&backup_common_dir).unwrap();
+        File::create(backup_common_dir.join("old.md")).unwrap();
+
+        cache.recover_interrupted_update().unwrap();
+
+        assert!(cache.pages_dir().join("pages/common/old.md").is_file());
+        assert!(!dir.path().join(TLDR_BACKUP_DIR).exists());
+    }
+
+    #[test]
+    fn test_lookup_page_with_outdated_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path(), false);
+        let common_dir = cache.pages_dir().join("pages/common");
+        fs::create_dir_all(&common_dir).unwrap();
+        File::create(common_dir.join("tar.md")).unwrap();
+        filetime::set_file_mtime(&common_dir, filetime::FileTime::from_unix_time(1_000, 0))
+            .unwrap();
+        assert!(cache.current_index().is_none());
+        cache.build_index().unwrap();
+
+        let languages = ["en".to_string()];
+        let cache = Cache::new(dir.path(), false);
+        assert!(cache.current_index().is_some());
+
+        // An outdated index is not rebuilt for a lookup
+        File::create(common_dir.join("ls.md")).unwrap();
+        filetime::set_file_mtime(&common_dir, filetime::FileTime::from_unix_time(2_000, 0))
+            .unwrap();
+        let cache = Cache::new(dir.path(), false);
+        assert!(cache
+            .lookup_page("ls", &languages, None, &[PlatformType::Linux])
+            .is_some());
+        assert!(cache.index.get().is_none());
+    }
+
+    #[test]
+    fn test_walk_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path(), false);
+        for page in ["common/tar.md", "linux/ip.md", "osx/brew.md", "LICENSE.md"] {
+            let path = cache.pages_dir().join("pages").join(page);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            File::create(path).unwrap();
+        }
+        let mut pages = cache.walk_pages(&["linux", "common"]);
+        pages.sort();
+        assert_eq!(pages, ["ip", "tar"]);
+    }
+
+    #[test]
+    fn test_find_page_without_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let common_dir = dir.path().join("pages.de/common");
+        fs::create_dir_all(&common_dir).unwrap();
+        File::create(common_dir.join("tar.md")).unwrap();
+        let lang_dirs = ["pages".to_string(), "pages.de".to_string()];
+
+        // Pages are still found if the index cannot be used
+        assert_eq!(
+            Cache::find_page_for_platform(None, "tar", dir.path(), "common", &lang_dirs),
+            Some(common_dir.join("tar.md"))
+        );
+        assert_eq!(
+            Cache::find_page_for_platform(None, "ls", dir.path(), "common", &lang_dirs),
+            None
+        );
+    }
 }
@@ -2,9 +2,9 @@
 
 use std::path::PathBuf;
 
-use clap::{arg, builder::ArgAction, command, ArgGroup, Parser};
+use clap::{builder::ArgAction, ArgGroup, Parser};
 
-use crate::types::{ColorOptions, PlatformType};
+use crate::types::{ColorOptions, OutputFormat, PlatformType};
 
 // Note: flag names are specified explicitly in clap attributes
 // to improve readability and allow contributors to grep names like "clear-cache"
@@ -32,6 +32,23 @@ pub(crate) struct Cli {
     #[arg(short = 'l', long = "list")]
     pub list: bool,
 
+    /// Show the platforms, custom page status and description of the listed pages
+    #[arg(long = "long", requires = "list")]
+    pub long: bool,
+
+    /// List the pages of all platforms
+    #[arg(long = "all-platforms", requires = "list")]
+    pub all_platforms: bool,
+
+    /// Search the descriptions and examples of all pages
+    #[arg(
+        short = 's',
+        long = "search",
+        value_name = "QUERY",
+        conflicts_with_all = ["command", "list"]
+    )]
+    pub search: Option<String>,
+
     /// Render a specific markdown file
     #[arg(
         short = 'f',
@@ -58,6 +75,11 @@ pub(crate) struct Cli {
     #[arg(short = 'u', long = "update")]
     pub update: bool,
 
+    /// Override the base URL to download the pages from, can be specified multiple times in order
+    /// of preference
+    #[arg(long = "archive-source", value_name = "URL", action = ArgAction::Append)]
+    pub archive_source: Option<Vec<String>>,
+
     /// If auto update is configured, disable it for this run
     #[arg(long = "no-auto-update", requires = "command_or_file")]
     pub no_auto_update: bool,
@@ -70,6 +92,40 @@ pub(crate) struct Cli {
     #[arg(long = "pager", requires = "command_or_file")]
     pub pager: bool,
 
+    /// Show the page of the original command if the page is an alias
+    #[arg(long = "follow-aliases", requires = "command")]
+    pub follow_aliases: bool,
+
+    /// Show only the example with the given number
+    #[arg(
+        long = "example",
+        value_name = "N",
+        requires = "command_or_file",
+        conflicts_with = "raw"
+    )]
+    pub example: Option<usize>,
+
+    /// Number the examples
+    #[arg(long = "numbered", requires = "command_or_file")]
+    pub numbered: bool,
+
+    /// Print only the code of the examples, without highlighting
+    #[arg(
+        long = "code-only",
+        requires = "command_or_file",
+        conflicts_with_all = ["raw", "format"]
+    )]
+    pub code_only: bool,
+
+    /// Fill in the placeholders of an example interactively and print the
+    /// resulting command
+    #[arg(
+        long = "fill",
+        requires = "command_or_file",
+        conflicts_with_all = ["raw", "format", "code_only"]
+    )]
+    pub fill: bool,
+
     /// Display the raw markdown instead of rendering it
     #[arg(short = 'r', long = "raw", requires = "command_or_file")]
     pub raw: bool,
@@ -82,6 +138,10 @@ pub(crate) struct Cli {
     #[arg(long = "show-paths")]
     pub show_paths: bool,
 
+    /// Show what is stored in the cache and where it was downloaded from
+    #[arg(long = "cache-info")]
+    pub cache_info: bool,
+
     /// Create a basic config
     #[arg(long = "seed-config")]
     pub seed_config: bool,
@@ -90,6 +150,15 @@ pub(crate) struct Cli {
     #[arg(long = "color", value_name = "WHEN")]
     pub color: Option<ColorOptions>,
 
+    /// The format of pages and page lists
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        default_value = "text",
+        conflicts_with = "raw"
+    )]
+    pub format: OutputFormat,
+
     /// Print the version
     // Note: We override the version flag because clap uses `-V` by default,
     // while TLDR specification requires `-v` to be used.
@@ -11,11 +11,15 @@ use log::debug;
 use serde_derive::{Deserialize, Serialize};
 use yansi::{Color, Style};
 
-use crate::types::PathSource;
+use crate::types::{OptionStyle, PathSource};
 
 pub const CONFIG_FILE_NAME: &str = "config.toml";
 pub const MAX_CACHE_AGE: Duration = Duration::from_secs(2_592_000); // 30 days
 const DEFAULT_UPDATE_INTERVAL_HOURS: u64 = MAX_CACHE_AGE.as_secs() / 3600; // 30 days
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_READ_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_DOWNLOAD_RETRIES: u32 = 2;
+const DEFAULT_ARCHIVE_SOURCE: &str = "https://github.com/tldr-pages/tldr/releases/latest/download/";
 
 fn default_underline() -> bool {
     false
@@ -127,6 +131,14 @@ struct RawStyleConfig {
     pub example_code: RawStyle,
     #[serde(default)]
     pub example_variable: RawStyle,
+    #[serde(default)]
+    pub url: RawStyle,
+    #[serde(default)]
+    pub inline_code: RawStyle,
+    #[serde(default)]
+    pub emphasis: RawStyle,
+    #[serde(default)]
+    pub strong: RawStyle,
 }
 
 impl From<RawStyleConfig> for StyleConfig {
@@ -137,6 +149,10 @@ impl From<RawStyleConfig> for StyleConfig {
             example_text: raw_style_config.example_text.into(),
             example_code: raw_style_config.example_code.into(),
             example_variable: raw_style_config.example_variable.into(),
+            url: raw_style_config.url.into(),
+            inline_code: raw_style_config.inline_code.into(),
+            emphasis: raw_style_config.emphasis.into(),
+            strong: raw_style_config.strong.into(),
         }
     }
 }
@@ -147,6 +163,14 @@ struct RawDisplayConfig {
     pub compact: bool,
     #[serde(default)]
     pub use_pager: bool,
+    #[serde(default)]
+    pub follow_aliases: bool,
+    #[serde(default)]
+    pub number_examples: bool,
+    #[serde(default)]
+    pub max_width: Option<usize>,
+    #[serde(default)]
+    pub option_style: OptionStyle,
 }
 
 impl From<RawDisplayConfig> for DisplayConfig {
@@ -154,6 +178,10 @@ impl From<RawDisplayConfig> for DisplayConfig {
         Self {
             compact: raw_display_config.compact,
             use_pager: raw_display_config.use_pager,
+            follow_aliases: raw_display_config.follow_aliases,
+            number_examples: raw_display_config.number_examples,
+            max_width: raw_display_config.max_width,
+            option_style: raw_display_config.option_style,
         }
     }
 }
@@ -166,12 +194,48 @@ const fn default_auto_update_interval_hours() -> u64 {
     DEFAULT_UPDATE_INTERVAL_HOURS
 }
 
+fn default_archive_source() -> Vec<String> {
+    vec![DEFAULT_ARCHIVE_SOURCE.to_string()]
+}
+
+const fn default_connect_timeout_secs() -> u64 {
+    DEFAULT_CONNECT_TIMEOUT_SECS
+}
+
+const fn default_read_timeout_secs() -> u64 {
+    DEFAULT_READ_TIMEOUT_SECS
+}
+
+const fn default_download_retries() -> u32 {
+    DEFAULT_DOWNLOAD_RETRIES
+}
+
+const fn default_show_progress() -> bool {
+    true
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 struct RawUpdatesConfig {
     #[serde(default)]
     pub auto_update: bool,
     #[serde(default = "default_auto_update_interval_hours")]
     pub auto_update_interval_hours: u64,
+    #[serde(default = "default_archive_source")]
+    pub archive_source: Vec<String>,
+    #[serde(default)]
+    pub languages: Option<Vec<String>>,
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    #[serde(default = "default_read_timeout_secs")]
+    pub read_timeout_secs: u64,
+    #[serde(default = "default_download_retries")]
+    pub download_retries: u32,
+    #[serde(default = "default_show_progress")]
+    pub show_progress: bool,
+    #[serde(default)]
+    pub proxy: Option<String>,
+    #[serde(default)]
+    pub ca_bundle: Option<PathBuf>,
 }
 
 impl Default for RawUpdatesConfig {
@@ -179,6 +243,14 @@ impl Default for RawUpdatesConfig {
         Self {
             auto_update: false,
             auto_update_interval_hours: DEFAULT_UPDATE_INTERVAL_HOURS,
+            archive_source: default_archive_source(),
+            languages: None,
+            connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
+            read_timeout_secs: DEFAULT_READ_TIMEOUT_SECS,
+            download_retries: DEFAULT_DOWNLOAD_RETRIES,
+            show_progress: default_show_progress(),
+            proxy: None,
+            ca_bundle: None,
         }
     }
 }
@@ -190,6 +262,14 @@ impl From<RawUpdatesConfig> for UpdatesConfig {
             auto_update_interval: Duration::from_secs(
                 raw_updates_config.auto_update_interval_hours * 3600,
             ),
+            archive_source: raw_updates_config.archive_source,
+            languages: raw_updates_config.languages,
+            connect_timeout: Duration::from_secs(raw_updates_config.connect_timeout_secs),
+            read_timeout: Duration::from_secs(raw_updates_config.read_timeout_secs),
+            download_retries: raw_updates_config.download_retries,
+            show_progress: raw_updates_config.show_progress,
+            proxy: raw_updates_config.proxy,
+            ca_bundle: raw_updates_config.ca_bundle,
         }
     }
 }
@@ -232,6 +312,10 @@ impl Default for RawConfig {
         raw_config.style.example_code.foreground = Some(RawColor::Cyan);
         raw_config.style.example_variable.foreground = Some(RawColor::Cyan);
         raw_config.style.example_variable.underline = true;
+        raw_config.style.url.underline = true;
+        raw_config.style.inline_code.foreground = Some(RawColor::Cyan);
+        raw_config.style.emphasis.italic = true;
+        raw_config.style.strong.bold = true;
 
         raw_config
     }
@@ -244,18 +328,48 @@ pub struct StyleConfig {
     pub example_text: Style,
     pub example_code: Style,
     pub example_variable: Style,
+    pub url: Style,
+    pub inline_code: Style,
+    pub emphasis: Style,
+    pub strong: Style,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct DisplayConfig {
     pub compact: bool,
     pub use_pager: bool,
+    /// Show the original page instead of pages that are aliases of it
+    pub follow_aliases: bool,
+    /// Prefix the examples with their number
+    pub number_examples: bool,
+    /// Wrap the output at this width, even if the terminal is wider
+    pub max_width: Option<usize>,
+    /// Which form of option placeholders to show
+    pub option_style: OptionStyle,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct UpdatesConfig {
     pub auto_update: bool,
     pub auto_update_interval: Duration,
+    /// Base URLs to download the pages from, tried in order
+    pub archive_source: Vec<String>,
+    /// Languages to download pages for. If not set, the languages are
+    /// derived from the environment.
+    pub languages: Option<Vec<String>>,
+    /// Maximum time to wait for a connection to be established
+    pub connect_timeout: Duration,
+    /// Maximum time to wait for data from an established connection
+    pub read_timeout: Duration,
+    /// How often a failed download is retried
+    pub download_retries: u32,
+    /// Whether to show the download progress on stderr
+    pub show_progress: bool,
+    /// Proxy to use for all downloads, overriding the proxy environment
+    /// variables
+    pub proxy: Option<String>,
+    /// PEM bundle with additional CA certificates to trust
+    pub ca_bundle: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -298,7 +412,18 @@ impl Config {
     fn from_raw(raw_config: RawConfig) -> Result<Self> {
         let style = raw_config.style.into();
         let display = raw_config.display.into();
-        let updates = raw_config.updates.into();
+        ensure!(
+            !raw_config.updates.archive_source.is_empty(),
+            "The `archive_source` option must contain at least one URL"
+        );
+        ensure!(
+            raw_config.updates.connect_timeout_secs > 0 && raw_config.updates.read_timeout_secs > 0,
+            "The `connect_timeout_secs` and `read_timeout_secs` options must be greater than 0"
+        );
+        let mut updates: UpdatesConfig = raw_config.updates.into();
+        if let Some(ca_bundle) = env::var_os("TEALDEER_CA_BUNDLE").filter(|path| !path.is_empty()) {
+            updates.ca_bundle = Some(PathBuf::from(ca_bundle));
+        }
 
         // Determine directories config. For this, we need to take some
         // additional factory into account, like env variables, or the
@@ -369,14 +494,23 @@ impl Config {
         // Load raw config
         let raw_config: RawConfig = if config_file_path.exists() && config_file_path.is_file() {
             let mut config_file = fs::File::open(&config_file_path).with_context(|| {
-                format!("Failed to open config file path at {:?}", &config_file_path)
+                format!(
+                    "Failed to open config file path at {}",
+                    config_file_path.display()
+                )
             })?;
             let mut contents = String::new();
             config_file.read_to_string(&mut contents).with_context(|| {
-                format!("Failed to read from config file at {:?}", &config_file_path)
+                format!(
+                    "Failed to read from config file at {}",
+                    config_file_path.display()
+                )
             })?;
             toml::from_str(&contents).with_context(|| {
-                format!("Failed to parse TOML config file at {config_file_path:?}")
+                format!(
+                    "Failed to parse TOML config file at {}",
+                    config_file_path.display()
+                )
             })?
         } else {
             RawConfig::new()
@@ -393,6 +527,10 @@ impl Config {
                 example_text: Style::default(),
                 example_code: Style::default(),
                 example_variable: Style::default(),
+                url: Style::default(),
+                inline_code: Style::default(),
+                emphasis: Style::default(),
+                strong: Style::default(),
             };
         }
 
@@ -412,7 +550,7 @@ pub fn get_config_dir() -> Result<(PathBuf, PathSource)> {
     // $TEALDEER_CONFIG_DIR env variable.
     if let Ok(value) = env::var("TEALDEER_CONFIG_DIR") {
         return Ok((PathBuf::from(value), PathSource::EnvVar));
-    };
+    }
 
     // Otherwise, fall back to the user config directory.
     let dirs = get_app_root(AppDataType::UserConfig, &crate::APP_INFO)
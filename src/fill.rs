@@ -0,0 +1,254 @@
+//! Interactive filling of example placeholders.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    config::StyleConfig,
+    page::{CodeSegment, Example, Page},
+    types::OptionStyle,
+};
+
+/// Let the user pick an example of a page (unless `example_number` is given)
+/// and enter a value for each of its placeholders. Return the code of the
+/// example with the placeholders replaced.
+///
+/// Prompts are written to `output`, answers are read from `input`. Pressing
+/// enter without a value keeps the text of the placeholder. Option
+/// placeholders are filled in according to `option_style`, with the long
+/// option if both forms are to be shown, so that the command can be run.
+pub fn fill_example(
+    page: &Page,
+    example_number: Option<usize>,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    style: &StyleConfig,
+    option_style: OptionStyle,
+) -> Result<String> {
+    let example = match example_number {
+        Some(number) => page.example(number)?,
+        None => choose_example(page, input, output, style, option_style)?,
+    };
+
+    let mut values: HashMap<String, String> = HashMap::new();
+    let mut command = String::new();
+    let option_style = match option_style {
+        OptionStyle::Both => OptionStyle::Long,
+        option_style => option_style,
+    };
+    for segment in example.code_segments(option_style) {
+        match segment {
+            CodeSegment::Literal(text) => command.push_str(&text),
+            CodeSegment::Placeholder(placeholder) => {
+                // Placeholders that occur multiple times are only asked for once
+                if !values.contains_key(&placeholder) {
+                    write!(output, "{}: ", style.example_variable.paint(&placeholder))
+                        .and_then(|()| output.flush())
+                        .context("Could not write to the terminal")?;
+                    let value = read_answer(input)?;
+                    let value = if value.is_empty() {
+                        placeholder.clone()
+                    } else {
+                        value
+                    };
+                    values.insert(placeholder.clone(), value);
+                }
+                command.push_str(&values[&placeholder]);
+            }
+        }
+    }
+    Ok(command)
+}
+
+/// List the examples of a page and ask the user to pick one.
+fn choose_example<'a>(
+    page: &'a Page,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    style: &StyleConfig,
+    option_style: OptionStyle,
+) -> Result<&'a Example> {
+    match page.examples.as_slice() {
+        [] => bail!("The page has no examples"),
+        [example] => return Ok(example),
+        _ => {}
+    }
+
+    for (number, example) in (1..).zip(&page.examples) {
+        writeln!(
+            output,
+            "  {}\n      {}",
+            style
+                .example_text
+                .paint(format!("{number}. {}", example.text)),
+            style.example_code.paint(example.plain_code(option_style))
+        )
+        .context("Could not write to the terminal")?;
+    }
+    loop {
+        write!(output, "Example (1-{}): ", page.examples.len())
+            .and_then(|()| output.flush())
+            .context("Could not write to the terminal")?;
+        let answer = read_answer(input)?;
+        match answer.parse().map(|number| page.example(number)) {
+            Ok(Ok(example)) => return Ok(example),
+            Ok(Err(_)) | Err(_) => {
+                writeln!(
+                    output,
+                    "Please enter a number between 1 and {}.",
+                    page.examples.len()
+                )
+                .context("Could not write to the terminal")?;
+            }
+        }
+    }
+}
+
+/// Read a line of input, without the line break.
+fn read_answer(input: &mut impl BufRead) -> Result<String> {
+    let mut answer = String::new();
+    let bytes_read = input
+        .read_line(&mut answer)
+        .context("Could not read from the terminal")?;
+    if bytes_read == 0 {
+        bail!("Aborted, no input was given");
+    }
+    Ok(answer.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use yansi::Style;
+
+    use super::*;
+
+    fn fill(contents: &str, example_number: Option<usize>, answers: &str) -> Result<String> {
+        fill_with_options(contents, example_number, answers, OptionStyle::Long)
+    }
+
+    fn fill_with_options(
+        contents: &str,
+        example_number: Option<usize>,
+        answers: &str,
+        option_style: OptionStyle,
+    ) -> Result<String> {
+        fill_with_output(contents, example_number, answers, option_style).0
+    }
+
+    /// Like [`fill_with_options`], but also return what was written to the
+    /// terminal.
+    fn fill_with_output(
+        contents: &str,
+        example_number: Option<usize>,
+        answers: &str,
+        option_style: OptionStyle,
+    ) -> (Result<String>, String) {
+        let page = Page::parse(contents.as_bytes()).unwrap();
+        let style = StyleConfig {
+            description: Style::default(),
+            command_name: Style::default(),
+            example_text: Style::default(),
+            example_code: Style::default(),
+            example_variable: Style::default(),
+            url: Style::default(),
+            inline_code: Style::default(),
+            emphasis: Style::default(),
+            strong: Style::default(),
+        };
+        let mut output = Vec::new();
+        let command = fill_example(
+            &page,
+            example_number,
+            &mut answers.as_bytes(),
+            &mut output,
+            &style,
+            option_style,
+        );
+        (command, String::from_utf8(output).unwrap())
+    }
+
+    const TAR: &str = "# tar\n\n> Archiving utility.\n\n\
+                       - Create an archive from files:\n\n\
+                       `tar cf {{target.tar}} {{file1}} {{file2}}`\n\n\
+                       - Extract an archive into a directory:\n\n\
+                       `tar xf {{source.tar}} -C {{directory}} && ls {{directory}}`\n";
+
+    #[test]
+    fn test_fill_example() {
+        assert_eq!(
+            fill(TAR, Some(1), "backup.tar\na.txt\nb.txt\n").unwrap(),
+            "tar cf backup.tar a.txt b.txt"
+        );
+        // Repeated placeholders are only asked for once
+        assert_eq!(
+            fill(TAR, Some(2), "backup.tar\n/tmp\n").unwrap(),
+            "tar xf backup.tar -C /tmp && ls /tmp"
+        );
+        // Empty answers keep the placeholder text
+        assert_eq!(
+            fill(TAR, Some(1), "\na.txt\n\n").unwrap(),
+            "tar cf target.tar a.txt file2"
+        );
+    }
+
+    #[test]
+    fn test_choose_example() {
+        // Invalid choices are asked again
+        assert_eq!(
+            fill(TAR, None, "0\nfoo\n2\nbackup.tar\n/tmp\n").unwrap(),
+            "tar xf backup.tar -C /tmp && ls /tmp"
+        );
+        // A single example is chosen automatically
+        assert_eq!(
+            fill("# ls\n\n- List files:\n\n`ls {{path}}`\n", None, "/tmp\n").unwrap(),
+            "ls /tmp"
+        );
+        assert!(fill("# ls\n\n> List files.\n", None, "").is_err());
+    }
+
+    #[test]
+    fn test_fill_example_end_of_input() {
+        assert_eq!(
+            fill(TAR, Some(1), "backup.tar\n").unwrap_err().to_string(),
+            "Aborted, no input was given"
+        );
+    }
+
+    #[test]
+    fn test_fill_option_placeholders() {
+        let page = "# tar\n\n- Extract an archive:\n\n\
+                    `tar {{[-x|--extract]}} {{[-f|--file]}} {{source.tar}}`\n";
+        assert_eq!(
+            fill_with_options(page, None, "a.tar\n", OptionStyle::Short).unwrap(),
+            "tar -x -f a.tar"
+        );
+        assert_eq!(
+            fill_with_options(page, None, "a.tar\n", OptionStyle::Both).unwrap(),
+            "tar --extract --file a.tar"
+        );
+
+        // The examples to choose from are listed in the configured style
+        let page = "# tar\n\n- Extract an archive:\n\n\
+                    `tar {{[-x|--extract]}} {{[-f|--file]}} {{source.tar}}`\n\n\
+                    - List an archive:\n\n`tar {{[-t|--list]}} {{[-f|--file]}} {{source.tar}}`\n";
+        let (command, output) = fill_with_output(page, None, "2\na.tar\n", OptionStyle::Short);
+        assert_eq!(command.unwrap(), "tar -t -f a.tar");
+        assert!(output.contains("  1. Extract an archive:\n      tar -x -f {{source.tar}}\n"));
+        assert!(output.contains("  2. List an archive:\n      tar -t -f {{source.tar}}\n"));
+    }
+
+    #[test]
+    fn test_fill_escaped_braces() {
+        let page = "# docker inspect\n\n- Show the ID of a container:\n\n\
+                    `docker inspect --format '\\{\\{.Id\\}\\}' {{container}}`\n\n\
+                    - Show the name of a container:\n\n\
+                    `docker inspect --format '\\{\\{.Name\\}\\}' {{container}}`\n";
+        let (command, output) = fill_with_output(page, None, "1\nweb\n", OptionStyle::Long);
+        assert_eq!(command.unwrap(), "docker inspect --format '{{.Id}}' web");
+        assert!(output.contains("      docker inspect --format '{{.Id}}' {{container}}\n"));
+    }
+}
@@ -1,8 +1,6 @@
-//! Functions related to formatting and printing lines from a `Tokenizer`.
+//! Functions related to formatting and printing pages.
 
-use log::debug;
-
-use crate::{extensions::FindFrom, types::LineType};
+use crate::{extensions::FindFrom, page::Page, types::OptionStyle};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// Represents a snippet from a page of a specific highlighting class.
@@ -12,75 +10,175 @@ pub enum PageSnippet<'a> {
     NormalCode(&'a str),
     Description(&'a str),
     Text(&'a str),
+    /// Code in backticks within a description or example text
+    InlineCode(&'a str),
+    /// Text in `*asterisks*` or `_underscores_`
+    Emphasis(&'a str),
+    /// Text in `**double asterisks**` or `__double underscores__`
+    Strong(&'a str),
+    /// A link to `url`, shown as `text`
+    Link {
+        text: &'a str,
+        url: &'a str,
+    },
     Linebreak,
 }
 
-impl<'a> PageSnippet<'a> {
+impl PageSnippet<'_> {
     pub fn is_empty(&self) -> bool {
         use PageSnippet::*;
 
         match self {
-            CommandName(s) | Variable(s) | NormalCode(s) | Description(s) | Text(s) => s.is_empty(),
+            CommandName(s) | Variable(s) | NormalCode(s) | Description(s) | Text(s)
+            | InlineCode(s) | Emphasis(s) | Strong(s) => s.is_empty(),
+            Link { text, .. } => text.is_empty(),
             Linebreak => false,
         }
     }
 }
 
-/// Parse the content of each line yielded by `lines` and yield `HighLightingSnippet`s accordingly.
-pub fn highlight_lines<L, F, E>(
-    lines: L,
+/// Yield the `PageSnippet`s of a page in the order they are displayed.
+///
+/// If an example number is given, only that example is yielded, without the
+/// description. If `number_examples` is set, the example texts are prefixed
+/// with their number. If `inline_markdown` is set, the inline markdown of the
+/// description and the example texts is highlighted, see [`highlight_text`].
+/// Option placeholders are shown according to `option_style`.
+pub fn highlight_page<F, E>(
+    page: &Page,
+    example_number: Option<usize>,
     process_snippet: &mut F,
     keep_empty_lines: bool,
+    number_examples: bool,
+    inline_markdown: bool,
+    option_style: OptionStyle,
 ) -> Result<(), E>
 where
-    L: Iterator<Item = LineType>,
     F: for<'snip> FnMut(PageSnippet<'snip>) -> Result<(), E>,
 {
-    let mut command = String::new();
-    for line in lines {
-        match line {
-            LineType::Empty => {
-                if keep_empty_lines {
-                    process_snippet(PageSnippet::Linebreak)?;
-                }
-            }
-            LineType::Title(title) => {
-                debug!("Ignoring title");
-
-                // This is safe as long as the parsed title is only the command,
-                // and the iterator yields values in order of appearance.
-                command = title;
-                debug!("Detected command name: {}", &command);
-            }
-            LineType::Description(text) => process_snippet(PageSnippet::Description(&text))?,
-            LineType::ExampleText(text) => process_snippet(PageSnippet::Text(&text))?,
-            LineType::ExampleCode(text) => {
-                process_snippet(PageSnippet::NormalCode("      "))?;
-                highlight_code(&command, &text, process_snippet)?;
-                process_snippet(PageSnippet::Linebreak)?;
-            }
-
-            LineType::Other(text) => debug!("Unknown line type: {:?}", text),
+    if example_number.is_none() {
+        if keep_empty_lines && !page.description.is_empty() {
+            process_snippet(PageSnippet::Linebreak)?;
+        }
+        for line in &page.description {
+            highlight_text(
+                line,
+                PageSnippet::Description,
+                inline_markdown,
+                process_snippet,
+            )?;
         }
     }
+    let examples = page
+        .examples
+        .iter()
+        .zip(1..)
+        .filter(|&(_, number)| example_number.map_or(true, |n| n == number));
+    for (example, number) in examples {
+        if keep_empty_lines {
+            process_snippet(PageSnippet::Linebreak)?;
+        }
+        if number_examples {
+            let text = format!("{number}. {}", example.text);
+            highlight_text(&text, PageSnippet::Text, inline_markdown, process_snippet)?;
+        } else {
+            highlight_text(
+                &example.text,
+                PageSnippet::Text,
+                inline_markdown,
+                process_snippet,
+            )?;
+        }
+        if keep_empty_lines {
+            process_snippet(PageSnippet::Linebreak)?;
+        }
+        process_snippet(PageSnippet::NormalCode("      "))?;
+        highlight_code(&page.title, &example.code, option_style, process_snippet)?;
+        process_snippet(PageSnippet::Linebreak)?;
+    }
     process_snippet(PageSnippet::Linebreak)?;
     Ok(())
 }
 
 /// Highlight code examples including user variables in {{ curly braces }}.
-fn highlight_code<'a, E>(
+///
+/// Escaped braces (`\{\{` and `\}\}`) are yielded as literal braces. Braces
+/// that don't form a variable, like a `{{` without a matching `}}`, are
+/// yielded as they are.
+///
+/// Option placeholders like `{{[-o|--output]}}` are not variables, they are
+/// yielded as `NormalCode` in the form given by `option_style`.
+pub fn highlight_code<'a, E>(
     command: &'a str,
     text: &'a str,
+    option_style: OptionStyle,
     process_snippet: &mut impl FnMut(PageSnippet<'a>) -> Result<(), E>,
 ) -> Result<(), E> {
-    let variable_splits = text
-        .split("}}")
-        .map(|s| s.split_once("{{").unwrap_or((s, "")));
-    for (code_segment, variable) in variable_splits {
-        highlight_code_segment(command, code_segment, process_snippet)?;
-        process_snippet(PageSnippet::Variable(variable))?;
+    // The start of the code that hasn't been yielded yet
+    let mut code_start = 0;
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        let rest = &text[i..];
+        let escaped_braces = if rest.starts_with(r"\{\{") {
+            Some("{{")
+        } else if rest.starts_with(r"\}\}") {
+            Some("}}")
+        } else {
+            None
+        };
+        if let Some(braces) = escaped_braces {
+            highlight_code_segment(command, &text[code_start..i], process_snippet)?;
+            process_snippet(PageSnippet::NormalCode(braces))?;
+            i += r"\{\{".len();
+            code_start = i;
+            continue;
+        }
+
+        if let Some(variable) = rest.strip_prefix("{{") {
+            // A variable ends at the next `}}`, unless another one starts
+            // before that
+            let end = variable
+                .find("}}")
+                .filter(|&end| !variable[..end].contains("{{"));
+            if let Some(end) = end {
+                highlight_code_segment(command, &text[code_start..i], process_snippet)?;
+                highlight_variable(&variable[..end], option_style, process_snippet)?;
+                i += end + "{{}}".len();
+                code_start = i;
+                continue;
+            }
+        }
+        i += c.len_utf8();
     }
-    Ok(())
+    highlight_code_segment(command, &text[code_start..], process_snippet)
+}
+
+/// Yield a variable (without the braces), or an option placeholder in the
+/// form given by `option_style`.
+fn highlight_variable<'a, E>(
+    variable: &'a str,
+    option_style: OptionStyle,
+    process_snippet: &mut impl FnMut(PageSnippet<'a>) -> Result<(), E>,
+) -> Result<(), E> {
+    match (split_option_placeholder(variable), option_style) {
+        (Some((short, _)), OptionStyle::Short) => process_snippet(PageSnippet::NormalCode(short)),
+        (Some((_, long)), OptionStyle::Long) => process_snippet(PageSnippet::NormalCode(long)),
+        (Some(_), OptionStyle::Both) => process_snippet(PageSnippet::NormalCode(variable)),
+        (None, _) => process_snippet(PageSnippet::Variable(variable)),
+    }
+}
+
+/// Split an option placeholder like `[-o|--output]` (without the braces) into
+/// the short and the long option.
+fn split_option_placeholder(variable: &str) -> Option<(&str, &str)> {
+    let (short, long) = variable
+        .strip_prefix('[')?
+        .strip_suffix(']')?
+        .split_once('|')?;
+    let is_option = |option: &str| {
+        option.starts_with('-') && !option.contains(|c: char| c.is_whitespace() || c == '|')
+    };
+    (is_option(short) && is_option(long)).then_some((short, long))
 }
 
 /// Yields `NormalCode` and `CommandName` in alternating order according to the occurrences of
@@ -112,6 +210,102 @@ fn highlight_code_segment<'a, E>(
     Ok(())
 }
 
+/// Yield a line of prose (a description line or an example text), followed by
+/// a `Linebreak`. The text is yielded as `plain` snippets, which are
+/// `Description` or `Text`.
+///
+/// If `inline_markdown` is set, code spans in backticks, emphasis and links
+/// (both `[text](url)` and `<url>`) are yielded as separate snippets.
+/// Otherwise, the text is yielded as it is.
+pub fn highlight_text<'a, E>(
+    text: &'a str,
+    plain: fn(&'a str) -> PageSnippet<'a>,
+    inline_markdown: bool,
+    process_snippet: &mut impl FnMut(PageSnippet<'a>) -> Result<(), E>,
+) -> Result<(), E> {
+    if inline_markdown {
+        let mut plain_start = 0;
+        let mut i = 0;
+        while let Some(c) = text[i..].chars().next() {
+            let previous = text[..i].chars().next_back();
+            if let Some((snippet, length)) = inline_markup(&text[i..], previous) {
+                process_snippet(plain(&text[plain_start..i]))?;
+                process_snippet(snippet)?;
+                i += length;
+                plain_start = i;
+            } else {
+                i += c.len_utf8();
+            }
+        }
+        process_snippet(plain(&text[plain_start..]))?;
+    } else {
+        process_snippet(plain(text))?;
+    }
+    process_snippet(PageSnippet::Linebreak)
+}
+
+/// Recognize inline markdown at the start of `text`, where `previous` is the
+/// character before it. Return the snippet and the length of the markdown.
+fn inline_markup(text: &str, previous: Option<char>) -> Option<(PageSnippet<'_>, usize)> {
+    if let Some(rest) = text.strip_prefix('`') {
+        let code = &rest[..rest.find('`')?];
+        return Some((PageSnippet::InlineCode(code), code.len() + 2));
+    }
+    if let Some(rest) = text.strip_prefix('<') {
+        let url = &rest[..rest.find('>')?];
+        return is_url(url).then_some((PageSnippet::Link { text: url, url }, url.len() + 2));
+    }
+    if let Some(rest) = text.strip_prefix('[') {
+        let (link_text, rest) = rest.split_once("](")?;
+        let url = &rest[..rest.find(')')?];
+        return (!link_text.is_empty() && !link_text.contains(']') && is_url(url)).then_some((
+            PageSnippet::Link {
+                text: link_text,
+                url,
+            },
+            link_text.len() + url.len() + 4,
+        ));
+    }
+
+    // Emphasis may not start within a word, to keep `snake_case` intact
+    if previous.is_some_and(char::is_alphanumeric) {
+        return None;
+    }
+    for delimiter in ["**", "__", "*", "_"] {
+        let Some(rest) = text.strip_prefix(delimiter) else {
+            continue;
+        };
+        if rest.starts_with(char::is_whitespace) || rest.starts_with(delimiter) {
+            continue;
+        }
+        // The closing delimiter has to end the emphasized text, and may not
+        // be followed by a letter or another delimiter character
+        let end = rest
+            .match_indices(delimiter)
+            .map(|(end, _)| end)
+            .find(|&end| {
+                let before = rest[..end].chars().next_back();
+                let after = rest[end + delimiter.len()..].chars().next();
+                before.is_some_and(|c| !c.is_whitespace())
+                    && !after.is_some_and(|c| c.is_alphanumeric() || delimiter.starts_with(c))
+            });
+        if let Some(end) = end {
+            let snippet = if delimiter.len() == 2 {
+                PageSnippet::Strong(&rest[..end])
+            } else {
+                PageSnippet::Emphasis(&rest[..end])
+            };
+            return Some((snippet, end + 2 * delimiter.len()));
+        }
+    }
+    None
+}
+
+/// Whether the text between angle brackets or parentheses is a URL.
+fn is_url(text: &str) -> bool {
+    text.contains("://") && !text.contains(char::is_whitespace)
+}
+
 /// Checks whether the characters right before and after the substring (given by half-open index interval) are whitespace (if they exist).
 fn is_freestanding_substring(surrounding: &str, substring: (usize, usize)) -> bool {
     let (start, end) = substring;
@@ -194,6 +388,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_highlight_code_escaped_braces() {
+        assert_eq!(
+            run_code(
+                r"docker inspect --format '\{\{.Id\}\}' {{container}}",
+                OptionStyle::Long
+            ),
+            [
+                NormalCode("docker inspect --format '"),
+                NormalCode("{{"),
+                NormalCode(".Id"),
+                NormalCode("}}"),
+                NormalCode("' "),
+                Variable("container")
+            ]
+        );
+        assert_eq!(
+            run_code(r"tar \{\{{{file}}\}\}", OptionStyle::Long),
+            [
+                CommandName("tar"),
+                NormalCode(" "),
+                NormalCode("{{"),
+                Variable("file"),
+                NormalCode("}}")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlight_code_unbalanced_braces() {
+        assert_eq!(
+            run_code("tar {{file", OptionStyle::Long),
+            [CommandName("tar"), NormalCode(" {{file")]
+        );
+        assert_eq!(
+            run_code("tar file}} {{dir}}", OptionStyle::Long),
+            [CommandName("tar"), NormalCode(" file}} "), Variable("dir")]
+        );
+        assert_eq!(
+            run_code("tar {{a {{b}}}", OptionStyle::Long),
+            [
+                CommandName("tar"),
+                NormalCode(" {{a "),
+                Variable("b"),
+                NormalCode("}")
+            ]
+        );
+        assert_eq!(
+            run_code("tar }}{{", OptionStyle::Long),
+            [CommandName("tar"), NormalCode(" }}{{")]
+        );
+    }
+
     #[test]
     fn test_i18n() {
         assert_eq!(
@@ -226,6 +473,165 @@ mod tests {
         );
     }
 
+    fn run_text<'a>(text: &'a str) -> Vec<PageSnippet<'a>> {
+        let mut yielded = Vec::new();
+        let mut process_snippet = |snip: PageSnippet<'a>| {
+            if !snip.is_empty() {
+                yielded.push(snip);
+            }
+            Ok::<(), ()>(())
+        };
+
+        highlight_text(text, Description, true, &mut process_snippet)
+            .expect("highlight text failed");
+        yielded
+    }
+
+    #[test]
+    fn test_highlight_text() {
+        assert_eq!(run_text(""), [Linebreak]);
+        assert_eq!(
+            run_text("Create a `tar` archive."),
+            [
+                Description("Create a "),
+                InlineCode("tar"),
+                Description(" archive."),
+                Linebreak
+            ]
+        );
+        assert_eq!(
+            run_text("This is *really* **not** _recommended_, __ever__."),
+            [
+                Description("This is "),
+                Emphasis("really"),
+                Description(" "),
+                Strong("not"),
+                Description(" "),
+                Emphasis("recommended"),
+                Description(", "),
+                Strong("ever"),
+                Description("."),
+                Linebreak
+            ]
+        );
+        // Unclosed markup is kept as it is
+        assert_eq!(
+            run_text("Count the ` characters *"),
+            [Description("Count the ` characters *"), Linebreak]
+        );
+    }
+
+    #[test]
+    fn test_highlight_text_not_emphasis() {
+        for text in [
+            "Print the value of some_environment_variable",
+            "Delete all *.log files in *nix systems",
+            "Compute 2 * 3 * 4",
+            "Use `*` and `_` as wildcards",
+        ] {
+            assert!(
+                run_text(text)
+                    .iter()
+                    .all(|snip| !matches!(snip, Emphasis(_) | Strong(_))),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_highlight_text_links() {
+        assert_eq!(
+            run_text("More information: <https://www.gnu.org/software/tar>."),
+            [
+                Description("More information: "),
+                Link {
+                    text: "https://www.gnu.org/software/tar",
+                    url: "https://www.gnu.org/software/tar"
+                },
+                Description("."),
+                Linebreak
+            ]
+        );
+        assert_eq!(
+            run_text("See the [manual](https://example.com/manual) or [u]ser"),
+            [
+                Description("See the "),
+                Link {
+                    text: "manual",
+                    url: "https://example.com/manual"
+                },
+                Description(" or [u]ser"),
+                Linebreak
+            ]
+        );
+        // Only URLs are recognized, not any text in angle brackets
+        assert_eq!(
+            run_text("Redirect <stdin> to <file>, see [u]ser (https://example.com)"),
+            [
+                Description("Redirect <stdin> to <file>, see [u]ser (https://example.com)"),
+                Linebreak
+            ]
+        );
+    }
+
+    fn run_code<'a>(code: &'a str, option_style: OptionStyle) -> Vec<PageSnippet<'a>> {
+        let mut yielded = Vec::new();
+        let mut process_snippet = |snip: PageSnippet<'a>| {
+            if !snip.is_empty() {
+                yielded.push(snip);
+            }
+            Ok::<(), ()>(())
+        };
+
+        highlight_code("tar", code, option_style, &mut process_snippet)
+            .expect("highlight code failed");
+        yielded
+    }
+
+    #[test]
+    fn test_option_placeholders() {
+        let code = "tar {{[-c|--create]}} {{[-f|--file]}} {{path/to/file.tar}}";
+        assert_eq!(
+            run_code(code, OptionStyle::Short),
+            [
+                CommandName("tar"),
+                NormalCode(" "),
+                NormalCode("-c"),
+                NormalCode(" "),
+                NormalCode("-f"),
+                NormalCode(" "),
+                Variable("path/to/file.tar")
+            ]
+        );
+        assert_eq!(
+            run_code(code, OptionStyle::Long)[2..5],
+            [
+                NormalCode("--create"),
+                NormalCode(" "),
+                NormalCode("--file")
+            ]
+        );
+        assert_eq!(
+            run_code(code, OptionStyle::Both)[2..5],
+            [
+                NormalCode("[-c|--create]"),
+                NormalCode(" "),
+                NormalCode("[-f|--file]")
+            ]
+        );
+        // Other placeholders in brackets are still variables
+        assert_eq!(
+            run_code("tar {{[-c|create]}} {{[a|b]}} {{[-x]}}", OptionStyle::Long)[2..],
+            [
+                Variable("[-c|create]"),
+                NormalCode(" "),
+                Variable("[a|b]"),
+                NormalCode(" "),
+                Variable("[-x]")
+            ]
+        );
+    }
+
     #[test]
     fn test_empty_command() {
         let segment = "some code";
@@ -0,0 +1,428 @@
+//! A persistent index of the pages in the cache.
+//!
+//! Looking up, listing and searching pages would otherwise require checking
+//! for the existence of files, walking the pages directory, or even reading
+//! every page, which is slow on network filesystems. The index is built after
+//! every update and stored in a simple line based format:
+//!
+//! ```text
+//! tealdeer-index 2
+//! dir     <language dir>[/<platform>]  <modification time in nanoseconds>
+//! page    <name>  <language dir>  <platform>  <summary>  <description>  <example words>
+//! ```
+//!
+//! Fields are separated by tabs. The modification times of the directories
+//! allow detecting pages that were added or removed since the index was
+//! built, in which case the index is rebuilt.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs::{self, File},
+    io::BufReader,
+    path::Path,
+    time::UNIX_EPOCH,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{line_iterator::LineIterator, types::LineType};
+
+/// The first line of an index file, changed whenever the format changes.
+const INDEX_HEADER: &str = "tealdeer-index 2";
+
+/// A page in the index.
+#[derive(Debug, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// The language directory the page is located in, e.g. `pages.de`
+    pub language_dir: String,
+    /// The platform directory the page is located in, e.g. `common`
+    pub platform: String,
+    /// The first line of the description
+    pub summary: String,
+    /// The description of the page, with multiple lines joined by spaces
+    pub description: String,
+    /// The lowercase words of the example texts, separated by spaces
+    example_words: String,
+}
+
+impl IndexEntry {
+    /// Return whether the page could contain all of the (lowercase) search
+    /// terms in its name, description or example texts.
+    ///
+    /// This never returns `false` for a page that contains the terms, but it
+    /// may return `true` for pages that do not. For example, the example
+    /// words `foo bar` match the term `o b`.
+    pub fn may_contain(&self, name: &str, terms: &[String]) -> bool {
+        let description = self.description.to_lowercase();
+        terms.iter().all(|term| {
+            name.contains(term.as_str())
+                || description.contains(term.as_str())
+                || words(term).all(|word| self.example_words.contains(word))
+        })
+    }
+}
+
+/// The index of all pages in a pages directory.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PageIndex {
+    /// The modification times of the language and platform directories
+    /// before the index was built
+    stamp: Vec<(String, u128)>,
+    /// The pages, keyed by name
+    pages: BTreeMap<String, Vec<IndexEntry>>,
+}
+
+impl PageIndex {
+    /// Build the index by reading all pages in `pages_dir`.
+    pub fn build(pages_dir: &Path) -> Result<Self> {
+        let mut index = Self {
+            stamp: stamp(pages_dir)?,
+            pages: BTreeMap::new(),
+        };
+
+        for (dir, _) in &index.stamp {
+            let Some((language_dir, platform)) = dir.split_once('/') else {
+                continue;
+            };
+            let platform_dir = pages_dir.join(language_dir).join(platform);
+            for entry in fs::read_dir(&platform_dir)
+                .with_context(|| format!("Could not read {}", platform_dir.display()))?
+            {
+                let path = entry?.path();
+                let Some(name) = path
+                    .file_name()
+                    .and_then(|file_name| file_name.to_str())
+                    .and_then(|file_name| file_name.strip_suffix(".md"))
+                else {
+                    continue;
+                };
+                if !path.is_file() {
+                    continue;
+                }
+
+                let file = File::open(&path)
+                    .with_context(|| format!("Could not open {}", path.display()))?;
+                let entry = index_page(
+                    language_dir,
+                    platform,
+                    LineIterator::new(BufReader::new(file)),
+                );
+                index.pages.entry(name.to_string()).or_default().push(entry);
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Load an index from a file. Returns `None` if the file is missing or
+    /// cannot be parsed.
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path).ok()?;
+        let mut lines = contents.lines();
+        if lines.next() != Some(INDEX_HEADER) {
+            return None;
+        }
+
+        let mut index = Self::default();
+        for line in lines {
+            let mut fields = line.split('\t');
+            match fields.next()? {
+                "dir" => {
+                    let dir = fields.next()?.to_string();
+                    let modified = fields.next()?.parse().ok()?;
+                    index.stamp.push((dir, modified));
+                }
+                "page" => {
+                    let name = fields.next()?.to_string();
+                    let entry = IndexEntry {
+                        language_dir: fields.next()?.to_string(),
+                        platform: fields.next()?.to_string(),
+                        summary: fields.next()?.to_string(),
+                        description: fields.next()?.to_string(),
+                        example_words: fields.next()?.to_string(),
+                    };
+                    index.pages.entry(name).or_default().push(entry);
+                }
+                _ => return None,
+            }
+        }
+        Some(index)
+    }
+
+    /// Write the index to a file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut contents = format!("{INDEX_HEADER}\n");
+        for (dir, modified) in &self.stamp {
+            let _ = writeln!(contents, "dir\t{dir}\t{modified}");
+        }
+        for (name, entries) in &self.pages {
+            for entry in entries {
+                let _ = writeln!(
+                    contents,
+                    "page\t{name}\t{}\t{}\t{}\t{}\t{}",
+                    entry.language_dir,
+                    entry.platform,
+                    entry.summary,
+                    entry.description,
+                    entry.example_words
+                );
+            }
+        }
+
+        // Write to a temporary file first, so that an interrupted write
+        // doesn't leave a truncated index behind
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, contents)
+            .and_then(|()| fs::rename(&tmp_path, path))
+            .with_context(|| format!("Could not write the page index to {}", path.display()))
+    }
+
+    /// Return whether the index still reflects the contents of `pages_dir`.
+    pub fn is_current(&self, pages_dir: &Path) -> bool {
+        stamp(pages_dir).is_ok_and(|stamp| stamp == self.stamp)
+    }
+
+    /// Return the page with the given name in a language and platform
+    /// directory.
+    pub fn get(&self, name: &str, language_dir: &str, platform: &str) -> Option<&IndexEntry> {
+        self.pages
+            .get(name)?
+            .iter()
+            .find(|entry| entry.language_dir == language_dir && entry.platform == platform)
+    }
+
+    /// Return all entries of the page with the given name.
+    pub fn entries(&self, name: &str) -> &[IndexEntry] {
+        self.pages.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Return the names of all pages in a language directory that are
+    /// available for one of the given platforms, in alphabetical order.
+    pub fn names<'a>(
+        &'a self,
+        language_dir: &'a str,
+        platforms: &'a [&str],
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        self.pages
+            .iter()
+            .filter(move |(_, entries)| {
+                entries.iter().any(|entry| {
+                    entry.language_dir == language_dir
+                        && platforms.contains(&entry.platform.as_str())
+                })
+            })
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+/// Collect the modification times of the language directories in
+/// `pages_dir` and of the platform directories within them.
+fn stamp(pages_dir: &Path) -> Result<Vec<(String, u128)>> {
+    let modified = |path: &Path| -> Result<u128> {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| {
+                format!(
+                    "Could not determine the modification time of {}",
+                    path.display()
+                )
+            })?;
+        Ok(modified
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos())
+    };
+    let subdirs = |path: &Path| -> Result<Vec<String>> {
+        let mut dirs = Vec::new();
+        for entry in
+            fs::read_dir(path).with_context(|| format!("Could not read {}", path.display()))?
+        {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                if let Ok(name) = entry.file_name().into_string() {
+                    dirs.push(name);
+                }
+            }
+        }
+        dirs.sort();
+        Ok(dirs)
+    };
+
+    let mut stamp = Vec::new();
+    for language_dir in subdirs(pages_dir)? {
+        let path = pages_dir.join(&language_dir);
+        stamp.push((language_dir.clone(), modified(&path)?));
+        for platform in subdirs(&path)? {
+            stamp.push((
+                format!("{language_dir}/{platform}"),
+                modified(&path.join(&platform))?,
+            ));
+        }
+    }
+    Ok(stamp)
+}
+
+/// Extract the indexed information from the lines of a page.
+fn index_page(
+    language_dir: &str,
+    platform: &str,
+    lines: impl Iterator<Item = LineType>,
+) -> IndexEntry {
+    let mut description: Vec<String> = Vec::new();
+    let mut example_words: Vec<String> = Vec::new();
+    for line in lines {
+        match line {
+            LineType::Description(text) => description.push(text),
+            LineType::ExampleText(text) => {
+                example_words.extend(words(&text.to_lowercase()).map(str::to_string));
+            }
+            _ => {}
+        }
+    }
+    example_words.sort();
+    example_words.dedup();
+
+    IndexEntry {
+        language_dir: language_dir.to_string(),
+        platform: platform.to_string(),
+        // Tabs would break the index format
+        summary: description
+            .first()
+            .map(|line| line.replace('\t', " "))
+            .unwrap_or_default(),
+        description: description.join(" ").replace('\t', " "),
+        example_words: example_words.join(" "),
+    }
+}
+
+/// Split a text into words, ignoring punctuation.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_page(pages_dir: &Path, path: &str, contents: &str) {
+        let path = pages_dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_build_save_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let pages_dir = dir.path().join("pages");
+        write_page(
+            &pages_dir,
+            "pages/common/tar.md",
+            "# tar\n\n> Archiving utility.\n> More information: <https://www.gnu.org/software/tar>.\n\n\
+             - Extract a (compressed) archive file:\n\n`tar xf {{source.tar}}`\n",
+        );
+        write_page(
+            &pages_dir,
+            "pages/linux/ip.md",
+            "# ip\n\n> Show\tinterfaces.\n",
+        );
+        write_page(
+            &pages_dir,
+            "pages.de/common/tar.md",
+            "# tar\n\n> Archivierungswerkzeug.\n",
+        );
+        write_page(&pages_dir, "pages/LICENSE.md", "MIT");
+
+        let index = PageIndex::build(&pages_dir).unwrap();
+        assert!(index.is_current(&pages_dir));
+        assert_eq!(
+            index.get("tar", "pages", "common"),
+            Some(&IndexEntry {
+                language_dir: "pages".to_string(),
+                platform: "common".to_string(),
+                summary: "Archiving utility.".to_string(),
+                description:
+                    "Archiving utility. More information: <https://www.gnu.org/software/tar>."
+                        .to_string(),
+                example_words: "a archive compressed extract file".to_string(),
+            })
+        );
+        assert_eq!(
+            index.get("ip", "pages", "linux").unwrap().description,
+            "Show interfaces."
+        );
+        assert!(index.get("tar", "pages.de", "common").is_some());
+        assert!(index.get("tar", "pages", "linux").is_none());
+        assert!(index.get("LICENSE", "pages", "common").is_none());
+
+        assert_eq!(
+            index
+                .names("pages", &["common", "linux"])
+                .collect::<Vec<_>>(),
+            ["ip", "tar"]
+        );
+        assert_eq!(
+            index.names("pages", &["common"]).collect::<Vec<_>>(),
+            ["tar"]
+        );
+        assert!(index.names("pages.de", &["linux"]).next().is_none());
+
+        let index_path = dir.path().join("pages.index");
+        index.save(&index_path).unwrap();
+        assert_eq!(PageIndex::load(&index_path), Some(index));
+
+        fs::write(&index_path, "tealdeer-index 1\n").unwrap();
+        assert_eq!(PageIndex::load(&index_path), None);
+        assert_eq!(PageIndex::load(&dir.path().join("missing")), None);
+    }
+
+    #[test]
+    fn test_is_current() {
+        let dir = tempfile::tempdir().unwrap();
+        let pages_dir = dir.path();
+        let set_mtime = |path: &str, seconds| {
+            filetime::set_file_mtime(
+                pages_dir.join(path),
+                filetime::FileTime::from_unix_time(seconds, 0),
+            )
+            .unwrap();
+        };
+        write_page(pages_dir, "pages/common/tar.md", "# tar");
+        set_mtime("pages/common", 1_000);
+        let index = PageIndex::build(pages_dir).unwrap();
+        assert!(index.is_current(pages_dir));
+
+        // Adding a page changes the modification time of its directory
+        write_page(pages_dir, "pages/common/ls.md", "# ls");
+        set_mtime("pages/common", 2_000);
+        assert!(!index.is_current(pages_dir));
+
+        // Adding a platform directory is noticed as well
+        let index = PageIndex::build(pages_dir).unwrap();
+        write_page(pages_dir, "pages/linux/ip.md", "# ip");
+        assert!(!index.is_current(pages_dir));
+    }
+
+    #[test]
+    fn test_may_contain() {
+        let entry = index_page(
+            "pages",
+            "common",
+            LineIterator::new(
+                "# tar\n\n> Archiving utility.\n\n- Extract files/directories:\n\n`tar xf {{path}}`\n"
+                    .as_bytes(),
+            ),
+        );
+        let terms = |terms: &[&str]| -> Vec<String> {
+            terms.iter().map(|term| (*term).to_string()).collect()
+        };
+        assert!(entry.may_contain("tar", &terms(&["tar"])));
+        assert!(entry.may_contain("tar", &terms(&["archiving", "utility"])));
+        assert!(entry.may_contain("tar", &terms(&["extract", "files/directories"])));
+        assert!(entry.may_contain("tar", &terms(&["dir"])));
+        assert!(!entry.may_contain("tar", &terms(&["extract", "zip"])));
+        // Example code is not indexed
+        assert!(!entry.may_contain("tar", &terms(&["xf"])));
+    }
+}
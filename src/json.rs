@@ -0,0 +1,113 @@
+//! Structured representation of pages for `--format json`.
+
+use std::path::PathBuf;
+
+use serde_derive::Serialize;
+
+use crate::{
+    cache::PageLookupResult,
+    page::{CodeSegment, Page},
+    types::{OptionStyle, PlatformType},
+};
+
+/// A page, as emitted by `--format json`.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct JsonPage {
+    pub title: String,
+    /// The lines of the description
+    pub description: Vec<String>,
+    /// The URL of the "More information" line of the description
+    pub more_info_url: Option<String>,
+    pub examples: Vec<JsonExample>,
+    /// The path of the page file
+    pub source_path: PathBuf,
+    /// The path of the custom patch appended to the page, if any
+    pub patch_path: Option<PathBuf>,
+    /// The platform the page was found for, `null` for common and custom
+    /// pages
+    pub platform: Option<PlatformType>,
+}
+
+/// An example of a page.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct JsonExample {
+    /// The text describing the example
+    pub text: String,
+    /// The example code, split into literal text and placeholders
+    pub code: Vec<CodeSegment>,
+}
+
+impl JsonPage {
+    /// Build the structured representation of a page. Option placeholders are
+    /// part of the literal code, in the form given by `option_style`.
+    pub fn new(page: Page, lookup_result: &PageLookupResult, option_style: OptionStyle) -> Self {
+        let examples = page
+            .examples
+            .into_iter()
+            .map(|example| JsonExample {
+                code: example.code_segments(option_style),
+                text: example.text,
+            })
+            .collect();
+        Self {
+            title: page.title,
+            description: page.description,
+            more_info_url: page.more_info_url,
+            examples,
+            source_path: lookup_result.page_path.clone(),
+            patch_path: lookup_result.patch_path.clone(),
+            platform: lookup_result.platform,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_page() {
+        let contents = "# tar\n\n\
+                        > Archiving utility.\n\
+                        > More information: <https://www.gnu.org/software/tar>.\n\n\
+                        - Extract a (compressed) archive file:\n\n\
+                        `tar xf {{source.tar}}`\n\n\
+                        - Display help:\n\n\
+                        `tar --help`\n";
+        let lookup_result = PageLookupResult::with_page(PathBuf::from("tar.md"));
+        let page = JsonPage::new(
+            Page::parse(contents.as_bytes()).unwrap(),
+            &lookup_result,
+            OptionStyle::Long,
+        );
+
+        assert_eq!(page.title, "tar");
+        assert_eq!(
+            page.description,
+            [
+                "Archiving utility.",
+                "More information: <https://www.gnu.org/software/tar>."
+            ]
+        );
+        assert_eq!(
+            page.more_info_url.as_deref(),
+            Some("https://www.gnu.org/software/tar")
+        );
+        assert_eq!(
+            page.examples,
+            [
+                JsonExample {
+                    text: "Extract a (compressed) archive file:".to_string(),
+                    code: vec![
+                        CodeSegment::Literal("tar xf ".to_string()),
+                        CodeSegment::Placeholder("source.tar".to_string()),
+                    ],
+                },
+                JsonExample {
+                    text: "Display help:".to_string(),
+                    code: vec![CodeSegment::Literal("tar --help".to_string())],
+                },
+            ]
+        );
+    }
+}
@@ -33,30 +33,49 @@ compile_error!(
 use std::{
     env,
     io::{self, IsTerminal},
+    path::Path,
     process,
 };
 
 use app_dirs::AppInfo;
 use clap::Parser;
+use log::debug;
 
+mod alias;
 mod cache;
 mod cli;
 mod config;
+mod download;
 pub mod extensions;
+mod fill;
 mod formatter;
+mod index;
+mod json;
 mod line_iterator;
 mod output;
+mod page;
+mod search;
+mod suggestions;
 mod types;
 mod utils;
+mod wrap;
 
 use crate::{
+    alias::{alias_target, Alias},
     cache::{Cache, CacheFreshness, PageLookupResult, TLDR_PAGES_DIR},
     cli::Cli,
     config::{get_config_dir, get_config_path, make_default_config, Config, PathWithSource},
+    download::Downloader,
     extensions::Dedup,
-    output::print_page,
-    types::{ColorOptions, PlatformType},
-    utils::{print_error, print_warning},
+    line_iterator::LineIterator,
+    output::{
+        print_filled_example, print_json, print_page, print_page_code, print_page_json,
+        print_page_list, print_search_results,
+    },
+    search::search_pages,
+    suggestions::suggest_pages,
+    types::{ColorOptions, OutputFormat, PlatformType},
+    utils::{format_duration, format_size, print_error, print_warning},
 };
 
 const NAME: &str = "tealdeer";
@@ -64,7 +83,6 @@ const APP_INFO: AppInfo = AppInfo {
     name: NAME,
     author: NAME,
 };
-const ARCHIVE_URL: &str = "https://tldr.sh/assets/tldr.zip";
 
 /// The cache should be updated if it was explicitly requested,
 /// or if an automatic update is due and allowed.
@@ -135,19 +153,78 @@ fn clear_cache(cache: &Cache, quietly: bool, enable_styles: bool) {
     }
 }
 
+/// Return the archive sources to update from, which may be overridden on
+/// the command line
+fn archive_sources<'a>(args: &'a Cli, config: &'a Config) -> &'a [String] {
+    args.archive_source
+        .as_ref()
+        .unwrap_or(&config.updates.archive_source)
+}
+
 /// Update the cache
-fn update_cache(cache: &Cache, quietly: bool, enable_styles: bool) {
-    cache.update(ARCHIVE_URL).unwrap_or_else(|e| {
-        print_error(enable_styles, &e.context("Could not update cache"));
-        process::exit(1);
-    });
-    if !quietly {
+fn update_cache(cache: &Cache, args: &Cli, config: &Config, enable_styles: bool) {
+    let archive_sources = archive_sources(args, config);
+    let languages = config
+        .updates
+        .languages
+        .clone()
+        .unwrap_or_else(get_languages_from_env);
+    Downloader::new(&config.updates, archive_sources, args.quiet, enable_styles)
+        .and_then(|downloader| cache.update(&downloader, archive_sources, &languages, args.quiet))
+        .unwrap_or_else(|e| {
+            print_error(enable_styles, &e.context("Could not update cache"));
+            process::exit(1);
+        });
+    if !args.quiet {
         eprintln!("Successfully updated cache.");
     }
 }
 
+/// Show what is stored in the cache and where it came from
+fn show_cache_info(cache: &Cache, archive_sources: &[String]) {
+    let mut pages_dir = cache.cache_dir().join(TLDR_PAGES_DIR);
+    pages_dir.push(""); // Trailing path separator
+    println!("Cache dir:        {}", cache.cache_dir().display());
+    println!("Pages dir:        {}", pages_dir.display());
+    for (i, source) in archive_sources.iter().enumerate() {
+        let label = if i == 0 { "Archive sources:" } else { "" };
+        println!("{label:<17} {source}");
+    }
+    if let CacheFreshness::Missing = cache.freshness() {
+        println!("Pages:            not present, run `tldr --update` to download them");
+        return;
+    }
+
+    let info = cache.info();
+    let metadata = info.metadata.unwrap_or_default();
+    let last_update = cache.last_update().map_or_else(
+        || "[Unknown]".to_string(),
+        |ago| format!("{} ago", format_duration(ago)),
+    );
+    let source = metadata.source.as_deref().unwrap_or("[Unknown]");
+    let tealdeer_version = metadata.tealdeer_version.as_deref().unwrap_or("[Unknown]");
+    let old_layout = if info.old_layout_present {
+        "present (will be removed by the next update)"
+    } else {
+        "not present"
+    };
+    println!("Last update:      {last_update}");
+    println!("Pages source:     {source}");
+    println!("Updated by:       tealdeer {tealdeer_version}");
+    println!("Disk usage:       {}", format_size(info.disk_usage));
+    println!("Old cache layout: {old_layout}");
+    println!("Pages:");
+    for (language, platforms) in &info.page_counts {
+        let total: usize = platforms.values().sum();
+        println!("  {language}: {total}");
+        for (platform, count) in platforms {
+            println!("    {platform:<12} {count:>6}");
+        }
+    }
+}
+
 /// Show file paths
-fn show_paths(config: &Config) {
+fn show_paths(config: &Config, cache: &Cache) {
     let config_dir = get_config_dir().map_or_else(
         |e| format!("[Error: {e}]"),
         |(mut path, source)| {
@@ -169,6 +246,16 @@ fn show_paths(config: &Config) {
         path.push(""); // Trailing path separator
         path.display().to_string()
     };
+    let pages_source = match cache.metadata() {
+        Some(metadata) => {
+            let source = metadata.source.as_deref().unwrap_or("[Unknown]");
+            match cache.last_update() {
+                Some(ago) => format!("{source} (updated {} ago)", format_duration(ago)),
+                None => source.to_string(),
+            }
+        }
+        None => "[Unknown]".to_string(),
+    };
     let custom_pages_dir = match config.directories.custom_pages_dir {
         Some(ref path_with_source) => path_with_source.to_string(),
         None => "[None]".to_string(),
@@ -177,9 +264,69 @@ fn show_paths(config: &Config) {
     println!("Config path:      {config_path}");
     println!("Cache dir:        {cache_dir}");
     println!("Pages dir:        {pages_dir}");
+    println!("Pages source:     {pages_source}");
     println!("Custom pages dir: {custom_pages_dir}");
 }
 
+/// Print a page in the format requested on the command line.
+fn show_page(
+    lookup_result: &PageLookupResult,
+    args: &Cli,
+    config: &Config,
+    enable_styles: bool,
+) -> anyhow::Result<()> {
+    if args.code_only {
+        return print_page_code(lookup_result, args.example, enable_styles, config);
+    }
+    if args.fill {
+        return print_filled_example(lookup_result, args.example, enable_styles, config);
+    }
+    match args.format {
+        OutputFormat::Text => print_page(
+            lookup_result,
+            args.example,
+            args.raw,
+            enable_styles,
+            args.pager,
+            config,
+        ),
+        OutputFormat::Json => print_page_json(lookup_result, args.example, enable_styles, config),
+    }
+}
+
+/// If the page is an alias page, look up the page of the original command
+/// instead. The page itself is returned if it is not an alias or if the
+/// original page cannot be found.
+fn follow_alias(
+    cache: &Cache,
+    lookup_result: PageLookupResult,
+    command: &str,
+    languages: &[String],
+    custom_pages_dir: Option<&Path>,
+    platforms: &[PlatformType],
+) -> PageLookupResult {
+    let Some(target) = lookup_result
+        .reader()
+        .ok()
+        .and_then(|reader| alias_target(LineIterator::new(reader)))
+    else {
+        return lookup_result;
+    };
+    if target == command {
+        return lookup_result;
+    }
+
+    if let Some(target_result) = cache.find_page(&target, languages, custom_pages_dir, platforms) {
+        target_result.with_alias(Alias {
+            name: command.to_string(),
+            target,
+        })
+    } else {
+        debug!("The page of `{target}`, which `{command}` is an alias of, was not found");
+        lookup_result
+    }
+}
+
 /// Create seed config file and exit
 fn create_config_and_exit(enable_styles: bool) {
     match make_default_config() {
@@ -268,7 +415,7 @@ fn main() {
     };
 
     // Look up config file, if none is found fall back to default config.
-    let config = match Config::load(enable_styles) {
+    let mut config = match Config::load(enable_styles) {
         Ok(config) => config,
         Err(e) => {
             print_error(enable_styles, &e.context("Could not load config"));
@@ -276,9 +423,16 @@ fn main() {
         }
     };
 
+    if args.numbered {
+        config.display.number_examples = true;
+    }
+
+    // Instantiate cache. This will not yet create the cache directory!
+    let cache = Cache::new(&config.directories.cache_dir.path, enable_styles);
+
     // Show various paths
     if args.show_paths {
-        show_paths(&config);
+        show_paths(&config, &cache);
     }
 
     // Create a basic config and exit
@@ -293,9 +447,9 @@ fn main() {
         .map_or(fallback_platforms, Vec::as_slice);
 
     // If a local file was passed in, render it and exit
-    if let Some(file) = args.render {
-        let path = PageLookupResult::with_page(file);
-        if let Err(ref e) = print_page(&path, args.raw, enable_styles, args.pager, &config) {
+    if let Some(ref file) = args.render {
+        let path = PageLookupResult::with_page(file.clone());
+        if let Err(ref e) = show_page(&path, &args, &config, enable_styles) {
             print_error(enable_styles, e);
             process::exit(1);
         } else {
@@ -303,9 +457,6 @@ fn main() {
         };
     }
 
-    // Instantiate cache. This will not yet create the cache directory!
-    let cache = Cache::new(&config.directories.cache_dir.path, enable_styles);
-
     // Clear cache, pass through
     if args.clear_cache {
         clear_cache(&cache, args.quiet, enable_styles);
@@ -313,7 +464,7 @@ fn main() {
 
     // Cache update, pass through
     let cache_updated = if should_update_cache(&cache, &args, &config) {
-        update_cache(&cache, args.quiet, enable_styles);
+        update_cache(&cache, &args, &config, enable_styles);
         true
     } else {
         false
@@ -321,12 +472,18 @@ fn main() {
 
     // Check cache presence and freshness
     if !cache_updated
-        && (args.list || !args.command.is_empty())
+        && (args.list || args.search.is_some() || !args.command.is_empty())
         && check_cache(&cache, &args, enable_styles) == CheckCacheResult::CacheMissing
     {
         process::exit(1);
     }
 
+    // Show cache information and exit
+    if args.cache_info {
+        show_cache_info(&cache, archive_sources(&args, &config));
+        process::exit(0);
+    }
+
     // List cached commands and exit
     if args.list {
         let custom_pages_dir = config
@@ -334,10 +491,56 @@ fn main() {
             .custom_pages_dir
             .as_ref()
             .map(PathWithSource::path);
-        println!(
-            "{}",
-            cache.list_pages(custom_pages_dir, platforms).join("\n")
-        );
+        let platforms = if args.all_platforms {
+            PlatformType::all()
+        } else {
+            platforms
+        };
+        if args.long || args.format == OutputFormat::Json {
+            let languages = args
+                .language
+                .clone()
+                .map_or_else(get_languages_from_env, |lang| vec![lang]);
+            let entries = cache.list_page_details(&languages, custom_pages_dir, platforms);
+            let result = match args.format {
+                OutputFormat::Text => print_page_list(&entries, &config),
+                OutputFormat::Json => print_json(&entries),
+            };
+            if let Err(ref e) = result {
+                print_error(enable_styles, e);
+                process::exit(1);
+            }
+        } else {
+            println!(
+                "{}",
+                cache.list_pages(custom_pages_dir, platforms).join("\n")
+            );
+        }
+        process::exit(0);
+    }
+
+    // Search pages and exit
+    if let Some(ref query) = args.search {
+        let custom_pages_dir = config
+            .directories
+            .custom_pages_dir
+            .as_ref()
+            .map(PathWithSource::path);
+        let languages = args
+            .language
+            .clone()
+            .map_or_else(get_languages_from_env, |lang| vec![lang]);
+        let results = search_pages(&cache, query, &languages, custom_pages_dir, platforms);
+        if results.is_empty() {
+            if !args.quiet {
+                print_warning(enable_styles, &format!("No pages found for `{query}`."));
+            }
+            process::exit(1);
+        }
+        if let Err(ref e) = print_search_results(&results, &config) {
+            print_error(enable_styles, e);
+            process::exit(1);
+        }
         process::exit(0);
     }
 
@@ -351,32 +554,52 @@ fn main() {
         // Collect languages
         let languages = args
             .language
+            .clone()
             .map_or_else(get_languages_from_env, |lang| vec![lang]);
 
+        let custom_pages_dir = config
+            .directories
+            .custom_pages_dir
+            .as_ref()
+            .map(PathWithSource::path);
+
         // Search for command in cache
-        if let Some(lookup_result) = cache.find_page(
-            &command,
-            &languages,
-            config
-                .directories
-                .custom_pages_dir
-                .as_ref()
-                .map(PathWithSource::path),
-            platforms,
-        ) {
-            if let Err(ref e) =
-                print_page(&lookup_result, args.raw, enable_styles, args.pager, &config)
-            {
+        if let Some(mut lookup_result) =
+            cache.find_page(&command, &languages, custom_pages_dir, platforms)
+        {
+            if args.follow_aliases || config.display.follow_aliases {
+                lookup_result = follow_alias(
+                    &cache,
+                    lookup_result,
+                    &command,
+                    &languages,
+                    custom_pages_dir,
+                    platforms,
+                );
+            }
+
+            if let Err(ref e) = show_page(&lookup_result, &args, &config, enable_styles) {
                 print_error(enable_styles, e);
                 process::exit(1);
             }
             process::exit(0);
         } else {
             if !args.quiet {
+                // Pages of all platforms can be found, so consider them all
+                let pages = cache.list_pages(custom_pages_dir, PlatformType::all());
+                let suggestions = suggest_pages(&command, &pages);
+                let did_you_mean = if suggestions.is_empty() {
+                    String::new()
+                } else {
+                    let suggestions: Vec<String> =
+                        suggestions.iter().map(|page| format!("`{page}`")).collect();
+                    format!("Did you mean {}?\n", suggestions.join(", "))
+                };
                 print_warning(
                     enable_styles,
                     &format!(
                         "Page `{}` not found in cache.\n\
+                         {did_you_mean}\
                          Try updating with `tldr --update`, or submit a pull request to:\n\
                          https://github.com/tldr-pages/tldr",
                         &command
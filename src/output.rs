@@ -1,14 +1,23 @@
 //! Functions for printing pages to the terminal
 
-use std::io::{self, BufRead, Write};
+use std::io::{self, BufRead, BufReader, Read, Write};
 
 use anyhow::{Context, Result};
+use serde::Serialize;
+use terminal_size::{terminal_size, Width};
+use unicode_width::UnicodeWidthStr;
+use yansi::Style;
 
 use crate::{
-    cache::PageLookupResult,
+    cache::{PageListEntry, PageLookupResult},
     config::{Config, StyleConfig},
-    formatter::{highlight_lines, PageSnippet},
-    line_iterator::LineIterator,
+    fill::fill_example,
+    formatter::{highlight_page, highlight_text, PageSnippet},
+    json::JsonPage,
+    page::Page,
+    search::SearchResult,
+    utils::print_warning,
+    wrap::{wrap_code, wrap_text, Line, CONTINUATION_MARKER},
 };
 
 /// Set up display pager
@@ -23,20 +32,65 @@ fn configure_pager(_: bool) {
 
 #[cfg(target_os = "windows")]
 fn configure_pager(enable_styles: bool) {
-    use crate::utils::print_warning;
     print_warning(enable_styles, "--pager flag not available on Windows!");
 }
 
+/// The contents of a page, as they are going to be printed
+enum PageContent {
+    /// The raw markdown of the page
+    Markdown(BufReader<Box<dyn Read>>),
+    Parsed(Page),
+}
+
+/// Read and parse the page (and patch) of a lookup result
+fn parse_page(lookup_result: &PageLookupResult) -> Result<Page> {
+    Page::parse(lookup_result.reader()?).with_context(|| {
+        format!(
+            "Could not parse the page at {}",
+            lookup_result.page_path.display()
+        )
+    })
+}
+
+/// Tell the user if the page is not meant for the requested platform
+fn warn_if_platform_fallback(lookup_result: &PageLookupResult, enable_styles: bool) {
+    if let (Some(platform), true) = (lookup_result.platform, lookup_result.is_platform_fallback) {
+        print_warning(
+            enable_styles,
+            &format!(
+                "The page is not available for the requested platform, \
+                 showing the page from {platform} instead."
+            ),
+        );
+    }
+}
+
 /// Print page by path
 pub fn print_page(
     lookup_result: &PageLookupResult,
+    example_number: Option<usize>,
     enable_markdown: bool,
     enable_styles: bool,
     use_pager: bool,
     config: &Config,
 ) -> Result<()> {
-    // Create reader from file(s)
-    let reader = lookup_result.reader()?;
+    // Read the page before printing anything, so that malformed pages don't
+    // result in partial output
+    let content = if enable_markdown {
+        PageContent::Markdown(lookup_result.reader()?)
+    } else {
+        let page = parse_page(lookup_result)?;
+        if let Some(number) = example_number {
+            page.example(number)?;
+        }
+        PageContent::Parsed(page)
+    };
+
+    warn_if_platform_fallback(lookup_result, enable_styles);
+
+    // The terminal width has to be determined before the pager takes over
+    // stdout
+    let width = output_width(config);
 
     // Configure pager if applicable
     if use_pager || config.display.use_pager {
@@ -45,52 +99,397 @@ pub fn print_page(
 
     // Lock stdout only once, this improves performance considerably
     let stdout = io::stdout();
-    let mut handle = stdout.lock();
+    let mut printer = SnippetPrinter::new(stdout.lock(), &config.style, width);
 
-    if enable_markdown {
-        // Print the raw markdown of the file.
-        for line in reader.lines() {
-            let line = line.context("Error while reading from a page")?;
-            writeln!(handle, "{line}").context("Could not write to stdout")?;
+    // Mention the alias page the user asked for, unless the output is
+    // supposed to be markdown
+    if let (Some(alias), PageContent::Parsed(_)) = (&lookup_result.alias, &content) {
+        if !config.display.compact {
+            printer
+                .print(PageSnippet::Linebreak)
+                .context("Could not write to stdout")?;
         }
-    } else {
-        // Closure that processes a page snippet and writes it to stdout
-        let mut process_snippet = |snip: PageSnippet<'_>| {
-            if snip.is_empty() {
-                Ok(())
-            } else {
-                print_snippet(&mut handle, snip, &config.style).context("Failed to print snippet")
+        let header = format!("`{}` is an alias of `{}`.", alias.name, alias.target);
+        highlight_text(
+            &header,
+            PageSnippet::Description,
+            enable_styles,
+            &mut |snip| printer.print(snip),
+        )
+        .context("Could not write to stdout")?;
+    }
+
+    match content {
+        PageContent::Markdown(reader) => {
+            // Print the raw markdown of the file.
+            for line in reader.lines() {
+                let line = line.context("Error while reading from a page")?;
+                writeln!(printer.writer, "{line}").context("Could not write to stdout")?;
             }
-        };
+        }
+        PageContent::Parsed(page) => {
+            // Closure that processes a page snippet and writes it to stdout
+            let mut process_snippet = |snip: PageSnippet<'_>| {
+                if snip.is_empty() {
+                    Ok(())
+                } else {
+                    printer.print(snip).context("Failed to print snippet")
+                }
+            };
+
+            // Print highlighted page
+            highlight_page(
+                &page,
+                example_number,
+                &mut process_snippet,
+                !config.display.compact,
+                config.display.number_examples,
+                enable_styles,
+                config.display.option_style,
+            )
+            .context("Could not write to stdout")?;
+        }
+    }
+
+    // We're done outputting data, flush stdout now!
+    printer.writer.flush().context("Could not flush stdout")?;
 
-        // Print highlighted lines
-        highlight_lines(
-            LineIterator::new(reader),
-            &mut process_snippet,
-            !config.display.compact,
+    Ok(())
+}
+
+/// Determine the width to wrap the output at: the width of the terminal, but
+/// at most the configured maximum width. If stdout is not a terminal, only
+/// the configured width is used.
+fn output_width(config: &Config) -> Option<usize> {
+    let terminal_width = terminal_size().map(|(Width(width), _)| usize::from(width));
+    match (terminal_width, config.display.max_width) {
+        (Some(terminal_width), Some(max_width)) => Some(terminal_width.min(max_width)),
+        (terminal_width, max_width) => terminal_width.or(max_width),
+    }
+}
+
+/// Print the structured representation of a page as JSON
+pub fn print_page_json(
+    lookup_result: &PageLookupResult,
+    example_number: Option<usize>,
+    enable_styles: bool,
+    config: &Config,
+) -> Result<()> {
+    let mut page = parse_page(lookup_result)?;
+    if let Some(number) = example_number {
+        page.examples = vec![page.example(number)?.clone()];
+    }
+
+    warn_if_platform_fallback(lookup_result, enable_styles);
+
+    print_json(&JsonPage::new(
+        page,
+        lookup_result,
+        config.display.option_style,
+    ))
+}
+
+/// Print the code of the examples of a page, one example per line, without
+/// any highlighting and with the placeholders intact
+pub fn print_page_code(
+    lookup_result: &PageLookupResult,
+    example_number: Option<usize>,
+    enable_styles: bool,
+    config: &Config,
+) -> Result<()> {
+    let page = parse_page(lookup_result)?;
+    let examples = match example_number {
+        Some(number) => vec![page.example(number)?],
+        None => page.examples.iter().collect(),
+    };
+
+    warn_if_platform_fallback(lookup_result, enable_styles);
+
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    for example in examples {
+        writeln!(
+            handle,
+            "{}",
+            example.plain_code(config.display.option_style)
         )
         .context("Could not write to stdout")?;
+    }
+    handle.flush().context("Could not flush stdout")?;
+
+    Ok(())
+}
+
+/// Let the user choose an example and fill in its placeholders on the
+/// terminal, then print the resulting command
+///
+/// The prompts are written to stderr, so that stdout only contains the command.
+pub fn print_filled_example(
+    lookup_result: &PageLookupResult,
+    example_number: Option<usize>,
+    enable_styles: bool,
+    config: &Config,
+) -> Result<()> {
+    let page = parse_page(lookup_result)?;
+    if let Some(number) = example_number {
+        page.example(number)?;
+    }
+
+    warn_if_platform_fallback(lookup_result, enable_styles);
+
+    let command = fill_example(
+        &page,
+        example_number,
+        &mut io::stdin().lock(),
+        &mut io::stderr().lock(),
+        &config.style,
+        config.display.option_style,
+    )?;
+
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    writeln!(handle, "{command}").context("Could not write to stdout")?;
+    handle.flush().context("Could not flush stdout")?;
+
+    Ok(())
+}
+
+/// Print a value as JSON, followed by a newline
+pub fn print_json(value: &impl Serialize) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+
+    serde_json::to_writer_pretty(&mut handle, value).context("Could not write JSON to stdout")?;
+    writeln!(handle).context("Could not write to stdout")?;
+    handle.flush().context("Could not flush stdout")?;
+
+    Ok(())
+}
+
+/// Print the details of pages in aligned columns, one page per line
+pub fn print_page_list(entries: &[PageListEntry], config: &Config) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+
+    let status = |entry: &PageListEntry| {
+        if entry.is_custom {
+            "custom"
+        } else if entry.is_patched {
+            "patched"
+        } else {
+            ""
+        }
     };
+    let rows: Vec<(&PageListEntry, String, &str)> = entries
+        .iter()
+        .map(|entry| (entry, entry.platforms.join(","), status(entry)))
+        .collect();
+    let name_width = rows
+        .iter()
+        .map(|(entry, _, _)| entry.name.width())
+        .max()
+        .unwrap_or(0);
+    let platforms_width = rows
+        .iter()
+        .map(|(_, platforms, _)| platforms.len())
+        .max()
+        .unwrap_or(0);
+    let status_width = rows
+        .iter()
+        .map(|(_, _, status)| status.len())
+        .max()
+        .unwrap_or(0);
+
+    for (entry, platforms, status) in rows {
+        let name = pad(&entry.name, name_width);
+        write!(
+            handle,
+            "{}  {platforms:<platforms_width$}",
+            config.style.command_name.paint(name)
+        )
+        .context("Could not write to stdout")?;
+        // Only show the status column if there are custom or patched pages
+        if status_width > 0 {
+            write!(handle, "  {status:<status_width$}").context("Could not write to stdout")?;
+        }
+        writeln!(
+            handle,
+            "  {}",
+            config.style.description.paint(&entry.description)
+        )
+        .context("Could not write to stdout")?;
+    }
+
+    handle.flush().context("Could not flush stdout")?;
+
+    Ok(())
+}
+
+/// Pad text with spaces to the given number of columns. Unlike formatting
+/// with a width, this takes wide characters into account.
+fn pad(text: &str, width: usize) -> String {
+    format!("{text}{:1$}", "", width.saturating_sub(text.width()))
+}
+
+/// Print search results, one page per line along with the matching line
+pub fn print_search_results(results: &[SearchResult], config: &Config) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+
+    let width = results
+        .iter()
+        .map(|result| result.page.width())
+        .max()
+        .unwrap_or(0);
+    for result in results {
+        let page = pad(&result.page, width);
+        writeln!(
+            handle,
+            "{}  {}",
+            config.style.command_name.paint(page),
+            config.style.description.paint(&result.line)
+        )
+        .context("Could not write to stdout")?;
+    }
 
-    // We're done outputting data, flush stdout now!
     handle.flush().context("Could not flush stdout")?;
 
     Ok(())
 }
 
-fn print_snippet(
+/// Prints page snippets, wrapping lines that are wider than the output
+struct SnippetPrinter<'a, W> {
+    writer: W,
+    style: &'a StyleConfig,
+    width: Option<usize>,
+    /// The pieces of the line that is being printed. Lines are printed (and
+    /// wrapped) as a whole when they are complete.
+    line: Vec<LinePiece>,
+    /// Whether the line that is being printed is example code
+    line_is_code: bool,
+}
+
+/// A piece of a line that is being printed
+struct LinePiece {
+    style: Style,
+    text: String,
+    /// Whether the line may be wrapped at whitespace within the piece
+    breakable: bool,
+    /// The target of the hyperlink, if the piece is a link
+    url: Option<String>,
+}
+
+impl<'a, W: Write> SnippetPrinter<'a, W> {
+    fn new(writer: W, style: &'a StyleConfig, width: Option<usize>) -> Self {
+        Self {
+            writer,
+            style,
+            width,
+            line: Vec::new(),
+            line_is_code: false,
+        }
+    }
+
+    fn print(&mut self, snip: PageSnippet<'_>) -> io::Result<()> {
+        use PageSnippet::*;
+
+        let style = self.style;
+        match snip {
+            CommandName(s) => self.push_code(style.command_name, s, false),
+            Variable(s) => self.push_code(style.example_variable, s, false),
+            NormalCode(s) => self.push_code(style.example_code, s, true),
+            Description(s) => self.push(style.description, s, true, None),
+            Text(s) => self.push(style.example_text, s, true, None),
+            InlineCode(s) => self.push(style.inline_code, s, true, None),
+            Emphasis(s) => self.push(style.emphasis, s, true, None),
+            Strong(s) => self.push(style.strong, s, true, None),
+            Link { text, url } => self.push(style.url, text, false, Some(url)),
+            Linebreak => {
+                self.print_line()?;
+                writeln!(self.writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, style: Style, text: &str, breakable: bool, url: Option<&str>) {
+        self.line.push(LinePiece {
+            style,
+            text: text.to_string(),
+            breakable,
+            url: url.map(str::to_string),
+        });
+    }
+
+    fn push_code(&mut self, style: Style, text: &str, breakable: bool) {
+        self.line_is_code = true;
+        self.push(style, text, breakable, None);
+    }
+
+    /// Print the current line, without the line break.
+    ///
+    /// Prose is indented by two spaces, which are kept on every line if the
+    /// text is wrapped. Code is continued on the next line after a
+    /// [`CONTINUATION_MARKER`], indented two spaces further than the code line
+    /// itself.
+    fn print_line(&mut self) -> io::Result<()> {
+        if self.line.is_empty() {
+            return Ok(());
+        }
+        let pieces: Vec<(&str, bool)> = self
+            .line
+            .iter()
+            .map(|piece| (piece.text.as_str(), piece.breakable))
+            .collect();
+        let (lines, indent, marker) = if self.line_is_code {
+            let indent = pieces.first().map_or(0, |(text, _)| {
+                text.len() - text.trim_start_matches(' ').len()
+            }) + 2;
+            let lines = wrap_code(&pieces, self.width.unwrap_or(usize::MAX), indent);
+            (lines, indent, Some(CONTINUATION_MARKER))
+        } else {
+            write!(self.writer, "  ")?;
+            let width = self
+                .width
+                .map_or(usize::MAX, |width| width.saturating_sub(2));
+            (wrap_text(&pieces, width, 0), 2, None)
+        };
+
+        for (number, line) in lines.into_iter().enumerate() {
+            if number > 0 {
+                // Only wrapped code lines get a continuation marker
+                if let Some(marker) = marker {
+                    write!(self.writer, "{}", self.style.example_code.paint(marker))?;
+                }
+                writeln!(self.writer)?;
+                write!(self.writer, "{:indent$}", "")?;
+            }
+            print_fragments(&mut self.writer, &self.line, &line)?;
+        }
+        self.line.clear();
+        self.line_is_code = false;
+        Ok(())
+    }
+}
+
+/// Print the fragments of a wrapped line, which refer to `pieces`
+fn print_fragments(
     writer: &mut impl Write,
-    snip: PageSnippet<'_>,
-    style: &StyleConfig,
+    pieces: &[LinePiece],
+    line: &Line<'_>,
 ) -> io::Result<()> {
-    use PageSnippet::*;
-
-    match snip {
-        CommandName(s) => write!(writer, "{}", style.command_name.paint(s)),
-        Variable(s) => write!(writer, "{}", style.example_variable.paint(s)),
-        NormalCode(s) => write!(writer, "{}", style.example_code.paint(s)),
-        Description(s) => writeln!(writer, "  {}", style.description.paint(s)),
-        Text(s) => writeln!(writer, "  {}", style.example_text.paint(s)),
-        Linebreak => writeln!(writer),
+    for &(piece, text) in line {
+        let piece = &pieces[piece];
+        match &piece.url {
+            // Print links as OSC 8 hyperlinks, which supporting terminals
+            // make clickable. Other terminals ignore the escape sequences.
+            Some(url) => write!(
+                writer,
+                "\x1b]8;;{url}\x1b\\{}\x1b]8;;\x1b\\",
+                piece.style.paint(text)
+            )?,
+            None => write!(writer, "{}", piece.style.paint(text))?,
+        }
     }
+    Ok(())
 }
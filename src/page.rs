@@ -0,0 +1,333 @@
+//! A structured representation of tldr pages.
+
+use std::io::BufRead;
+
+use anyhow::{bail, Result};
+use log::debug;
+
+use serde_derive::Serialize;
+
+use crate::{
+    formatter::{highlight_code, PageSnippet},
+    line_iterator::LineIterator,
+    types::{LineType, OptionStyle},
+};
+
+/// A parsed tldr page.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Page {
+    /// The name of the command, e.g. `git commit`
+    pub title: String,
+    /// The lines of the description, including the "More information" line
+    pub description: Vec<String>,
+    /// The URL of the "More information" line of the description, if any
+    pub more_info_url: Option<String>,
+    pub examples: Vec<Example>,
+}
+
+/// An example of a page: a text describing it, followed by the code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub text: String,
+    /// The code of the example, with `{{placeholders}}` intact
+    pub code: String,
+}
+
+/// A part of the code of an example.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(tag = "type", content = "text", rename_all = "lowercase")]
+pub enum CodeSegment {
+    /// Code that is meant to be typed as it is
+    Literal(String),
+    /// A placeholder (`{{...}}`) to be replaced by the user, without braces
+    Placeholder(String),
+}
+
+impl Example {
+    /// Split the code into literal and placeholder segments, the same way it
+    /// is highlighted. Option placeholders are part of the literal segments,
+    /// in the form given by `option_style`.
+    pub fn code_segments(&self, option_style: OptionStyle) -> Vec<CodeSegment> {
+        let mut segments = Vec::new();
+        let mut process_snippet = |snip: PageSnippet<'_>| {
+            if snip.is_empty() {
+                return Ok::<(), ()>(());
+            }
+            match (snip, segments.last_mut()) {
+                (
+                    PageSnippet::CommandName(s) | PageSnippet::NormalCode(s),
+                    Some(CodeSegment::Literal(literal)),
+                ) => literal.push_str(s),
+                (PageSnippet::CommandName(s) | PageSnippet::NormalCode(s), _) => {
+                    segments.push(CodeSegment::Literal(s.to_string()));
+                }
+                (PageSnippet::Variable(s), _) => {
+                    segments.push(CodeSegment::Placeholder(s.to_string()));
+                }
+                _ => {}
+            }
+            Ok(())
+        };
+        // The command name doesn't matter, since it is part of a literal
+        // segment either way. Collecting the segments cannot fail.
+        let _ = highlight_code("", &self.code, option_style, &mut process_snippet);
+        segments
+    }
+
+    /// Return the code as it is meant to be shown without highlighting: the
+    /// placeholders are kept in braces, while option placeholders are given
+    /// in the form given by `option_style`.
+    pub fn plain_code(&self, option_style: OptionStyle) -> String {
+        self.code_segments(option_style)
+            .into_iter()
+            .map(|segment| match segment {
+                CodeSegment::Literal(text) => text,
+                CodeSegment::Placeholder(placeholder) => format!("{{{{{placeholder}}}}}"),
+            })
+            .collect()
+    }
+}
+
+impl Page {
+    /// Parse a page in either the current or the old page format.
+    ///
+    /// An error is returned if the page has no title, more than one title,
+    /// description lines after the examples or examples that do not consist
+    /// of exactly one text and one code line.
+    pub fn parse(reader: impl BufRead) -> Result<Self> {
+        Self::from_lines(LineIterator::new(reader))
+    }
+
+    /// Build a page from the lines yielded by a [`LineIterator`].
+    pub fn from_lines(lines: impl IntoIterator<Item = LineType>) -> Result<Self> {
+        let mut title: Option<String> = None;
+        let mut page = Self::default();
+        // The text of an example whose code hasn't been seen yet
+        let mut pending_text: Option<String> = None;
+
+        for line in lines {
+            match line {
+                LineType::Empty => {}
+                LineType::Title(text) => {
+                    if let Some(title) = title {
+                        bail!("The page has a second title `{text}` after `{title}`");
+                    }
+                    title = Some(text);
+                }
+                LineType::Description(text) => {
+                    if !page.examples.is_empty() || pending_text.is_some() {
+                        bail!("The description line `{text}` comes after the examples");
+                    }
+                    if let Some(url) = more_info_url(&text) {
+                        page.more_info_url = Some(url.to_string());
+                    }
+                    page.description.push(text);
+                }
+                LineType::ExampleText(text) => {
+                    if let Some(previous) = pending_text.replace(text) {
+                        bail!("The example `{previous}` has no code");
+                    }
+                }
+                LineType::ExampleCode(code) => {
+                    let Some(text) = pending_text.take() else {
+                        bail!("The example code `{code}` has no description");
+                    };
+                    page.examples.push(Example { text, code });
+                }
+                LineType::Other(text) => debug!("Unknown line type: {:?}", text),
+            }
+        }
+
+        if let Some(text) = pending_text {
+            bail!("The example `{text}` has no code");
+        }
+        let Some(title) = title else {
+            bail!("The page has no title");
+        };
+        page.title = title;
+        Ok(page)
+    }
+
+    /// Return the example with the given number, starting at 1.
+    pub fn example(&self, number: usize) -> Result<&Example> {
+        match number.checked_sub(1).and_then(|i| self.examples.get(i)) {
+            Some(example) => Ok(example),
+            None if self.examples.len() == 1 => {
+                bail!("Example {number} not found, the page has only 1 example")
+            }
+            None => bail!(
+                "Example {number} not found, the page has {} examples",
+                self.examples.len()
+            ),
+        }
+    }
+}
+
+/// Return the URL of a "More information: <url>." description line. Since
+/// the line is translated in the pages of other languages, any line that ends
+/// with a URL in angle brackets is accepted.
+fn more_info_url(line: &str) -> Option<&str> {
+    let line = line.strip_suffix('.').unwrap_or(line).strip_suffix('>')?;
+    let (prefix, url) = line.rsplit_once('<')?;
+    (prefix.trim_end().ends_with(':') && url.contains("://")).then_some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Result<Page> {
+        Page::parse(contents.as_bytes())
+    }
+
+    #[test]
+    fn test_parse() {
+        let page = parse(
+            "# tar\n\n\
+             > Archiving utility.\n\
+             > More information: <https://www.gnu.org/software/tar>.\n\n\
+             - Create an archive from files:\n\n\
+             `tar cf {{target.tar}} {{file1}}`\n\n\
+             - Extract an archive:\n\n\
+             `tar xf {{source.tar}}`\n",
+        )
+        .unwrap();
+        assert_eq!(
+            page,
+            Page {
+                title: "tar".to_string(),
+                description: vec![
+                    "Archiving utility.".to_string(),
+                    "More information: <https://www.gnu.org/software/tar>.".to_string(),
+                ],
+                more_info_url: Some("https://www.gnu.org/software/tar".to_string()),
+                examples: vec![
+                    Example {
+                        text: "Create an archive from files:".to_string(),
+                        code: "tar cf {{target.tar}} {{file1}}".to_string(),
+                    },
+                    Example {
+                        text: "Extract an archive:".to_string(),
+                        code: "tar xf {{source.tar}}".to_string(),
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_new_format() {
+        let page = parse(
+            "tar\n===\n\n\
+             > Archiving utility.\n\n\
+             Extract an archive:\n\n    tar xf {{source.tar}}\n",
+        )
+        .unwrap();
+        assert_eq!(page.title, "tar");
+        assert_eq!(page.description, ["Archiving utility."]);
+        assert_eq!(page.more_info_url, None);
+        assert_eq!(page.examples[0].code, "tar xf {{source.tar}}");
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        let error = |contents| parse(contents).unwrap_err().to_string();
+        assert_eq!(error(""), "The page has no title");
+        assert_eq!(
+            error("# tar\n# gnu-tar\n"),
+            "The page has a second title `gnu-tar` after `tar`"
+        );
+        assert_eq!(
+            error("# tar\n\n- Extract:\n\n`tar xf`\n\n> Archiving utility.\n"),
+            "The description line `Archiving utility.` comes after the examples"
+        );
+        assert_eq!(
+            error("# tar\n\n- Extract:\n\n- Create:\n\n`tar cf`\n"),
+            "The example `Extract:` has no code"
+        );
+        assert_eq!(
+            error("# tar\n\n- Extract:\n"),
+            "The example `Extract:` has no code"
+        );
+        assert_eq!(
+            error("# tar\n\n- Extract:\n\n`tar xf`\n\n`tar xvf`\n"),
+            "The example code `tar xvf` has no description"
+        );
+    }
+
+    #[test]
+    fn test_example() {
+        let page = parse("# tar\n\n- Create:\n\n`tar cf`\n\n- Extract:\n\n`tar xf`\n").unwrap();
+        assert_eq!(page.example(2).unwrap().code, "tar xf");
+        assert_eq!(
+            page.example(3).unwrap_err().to_string(),
+            "Example 3 not found, the page has 2 examples"
+        );
+        assert!(page.example(0).is_err());
+    }
+
+    #[test]
+    fn test_code_segments() {
+        let example = |code: &str| Example {
+            text: String::new(),
+            code: code.to_string(),
+        };
+        assert_eq!(
+            example("tar xf {{source.tar}} -C {{directory}}").code_segments(OptionStyle::Long),
+            [
+                CodeSegment::Literal("tar xf ".to_string()),
+                CodeSegment::Placeholder("source.tar".to_string()),
+                CodeSegment::Literal(" -C ".to_string()),
+                CodeSegment::Placeholder("directory".to_string()),
+            ]
+        );
+        assert_eq!(
+            example("tar {{[-x|--extract]}} {{[-f|--file]}} {{source.tar}}")
+                .code_segments(OptionStyle::Short),
+            [
+                CodeSegment::Literal("tar -x -f ".to_string()),
+                CodeSegment::Placeholder("source.tar".to_string()),
+            ]
+        );
+        assert!(example("").code_segments(OptionStyle::Long).is_empty());
+    }
+
+    #[test]
+    fn test_plain_code() {
+        let example = Example {
+            text: String::new(),
+            code: r"docker inspect --format '\{\{.Id\}\}' {{container}}".to_string(),
+        };
+        assert_eq!(
+            example.plain_code(OptionStyle::Long),
+            "docker inspect --format '{{.Id}}' {{container}}"
+        );
+
+        let example = Example {
+            text: String::new(),
+            code: "tar {{[-x|--extract]}} {{[-f|--file]}} {{source.tar}}".to_string(),
+        };
+        assert_eq!(
+            example.plain_code(OptionStyle::Short),
+            "tar -x -f {{source.tar}}"
+        );
+        assert_eq!(
+            example.plain_code(OptionStyle::Both),
+            "tar [-x|--extract] [-f|--file] {{source.tar}}"
+        );
+    }
+
+    #[test]
+    fn test_more_info_url() {
+        assert_eq!(
+            more_info_url("More information: <https://example.com>."),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            more_info_url("Больше информации: <https://example.com/chmod>."),
+            Some("https://example.com/chmod")
+        );
+        assert_eq!(more_info_url("Archiving utility."), None);
+        assert_eq!(more_info_url("Compare with <tar>."), None);
+    }
+}
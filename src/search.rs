@@ -0,0 +1,201 @@
+//! Full-text search across pages.
+
+use std::{io::BufRead, path::Path};
+
+use log::debug;
+
+use crate::{
+    cache::Cache,
+    line_iterator::LineIterator,
+    types::{LineType, PlatformType},
+};
+
+/// A page matching a search query.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SearchResult {
+    /// The name of the page
+    pub page: String,
+    /// The line of the page that matches the query best
+    pub line: String,
+    /// How well the page matches, higher is better
+    score: u32,
+}
+
+/// Search the names, descriptions and example texts of all pages available
+/// for the given platforms. All words of the query must occur in a page for
+/// it to match.
+///
+/// The results are ordered by relevance: Pages whose name contains the query
+/// words come first, followed by pages with a single line containing as many
+/// of the words as possible.
+pub fn search_pages(
+    cache: &Cache,
+    query: &str,
+    languages: &[String],
+    custom_pages_dir: Option<&Path>,
+    platforms: &[PlatformType],
+) -> Vec<SearchResult> {
+    let terms = query_terms(query);
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut results: Vec<SearchResult> = cache
+        .list_pages(custom_pages_dir, platforms)
+        .into_iter()
+        .filter_map(|page| {
+            let lookup_result = cache.lookup_page(&page, languages, custom_pages_dir, platforms)?;
+            // Only read the pages that may match according to the index
+            if let Some(entry) = cache.indexed_page(&lookup_result) {
+                if !entry.may_contain(&page, &terms) {
+                    return None;
+                }
+            }
+            let reader = lookup_result
+                .reader()
+                .map_err(|e| debug!("Skipping page {} in search: {:#}", page, e))
+                .ok()?;
+            search_page(page, LineIterator::new(reader), &terms)
+        })
+        .collect();
+    results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.page.cmp(&b.page)));
+    results
+}
+
+/// Split a query into lowercase words.
+fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// Match a single page against the (lowercase) search terms.
+fn search_page(
+    page: String,
+    lines: LineIterator<impl BufRead>,
+    terms: &[String],
+) -> Option<SearchResult> {
+    // Which of the terms have been found so far, starting with the page name
+    let mut found: Vec<bool> = terms
+        .iter()
+        .map(|term| page.contains(term.as_str()))
+        .collect();
+    let name_score =
+        u32::try_from(found.iter().filter(|&&found| found).count()).unwrap_or(u32::MAX);
+
+    // The line containing the most terms, and how many it contains
+    let mut best_line: Option<(usize, bool, String)> = None;
+    let mut first_description = None;
+    for line in lines {
+        let (text, is_description) = match line {
+            LineType::ExampleText(text) => (text, false),
+            LineType::Description(text) => (text, true),
+            _ => continue,
+        };
+        if is_description && first_description.is_none() {
+            first_description = Some(text.clone());
+        }
+
+        let lowercase = text.to_lowercase();
+        let mut line_matches = 0;
+        for (term, found) in terms.iter().zip(found.iter_mut()) {
+            if lowercase.contains(term.as_str()) {
+                line_matches += 1;
+                *found = true;
+            }
+        }
+        if line_matches > best_line.as_ref().map_or(0, |(matches, _, _)| *matches) {
+            best_line = Some((line_matches, is_description, text));
+        }
+    }
+
+    if !found.iter().all(|&found| found) {
+        return None;
+    }
+
+    let (line_score, line) = match best_line {
+        Some((matches, is_description, line)) => (
+            u32::try_from(matches)
+                .unwrap_or(u32::MAX)
+                .saturating_mul(5)
+                .saturating_add(if is_description { 2 } else { 0 }),
+            line,
+        ),
+        None => (0, first_description.unwrap_or_default()),
+    };
+    let exact_name_score = if page == terms.join("-") { 20 } else { 0 };
+    Some(SearchResult {
+        page,
+        line,
+        score: name_score
+            .saturating_mul(10)
+            .saturating_add(line_score)
+            .saturating_add(exact_name_score),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(page: &str, contents: &str, query: &str) -> Option<SearchResult> {
+        search_page(
+            page.to_string(),
+            LineIterator::new(contents.as_bytes()),
+            &query_terms(query),
+        )
+    }
+
+    const TAR: &str = "# tar\n\n\
+                       > Archiving utility.\n\n\
+                       - Create an archive from files:\n\n\
+                       `tar cf {{target.tar}} {{file1}}`\n\n\
+                       - Extract a (compressed) archive file into the current directory:\n\n\
+                       `tar xf {{source.tar}}`\n";
+
+    #[test]
+    fn test_query_terms() {
+        assert_eq!(
+            query_terms("  Extract archive extract "),
+            ["archive", "extract"]
+        );
+        assert!(query_terms(" ").is_empty());
+    }
+
+    #[test]
+    fn test_search_page() {
+        let result = search("tar", TAR, "extract ARCHIVE").unwrap();
+        assert_eq!(result.page, "tar");
+        assert_eq!(
+            result.line,
+            "Extract a (compressed) archive file into the current directory:"
+        );
+
+        // All terms must occur in the page, but not necessarily in one line
+        assert!(search("tar", TAR, "extract archive zip").is_none());
+        let result = search("tar", TAR, "utility create").unwrap();
+        assert_eq!(result.line, "Archiving utility.");
+
+        // Example code is not searched
+        assert!(search("tar", TAR, "xf").is_none());
+    }
+
+    #[test]
+    fn test_search_page_ranking() {
+        let unzip = "# unzip\n\n> Extract files/directories from ZIP archives.\n";
+        let zip = "# zip\n\n> Package and compress files into ZIP archives.\n\n\
+                   - Extract files from an archive:\n";
+        let score = |page, contents, query| search(page, contents, query).unwrap().score;
+
+        // Matches in descriptions rank higher than matches in examples
+        assert!(score("unzip", unzip, "extract archive") > score("zip", zip, "extract archive"));
+        // Matches in the page name rank highest
+        assert!(score("zip", zip, "zip") > score("unzip", unzip, "zip"));
+        // The line shown for a name match is the description
+        assert_eq!(
+            search("zip", zip, "zip").unwrap().line,
+            "Package and compress files into ZIP archives."
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! Suggestions for page names that could not be found.
+
+/// The maximum number of suggestions to return.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Return the names of the pages that are most similar to `name`, best match
+/// first.
+///
+/// A page is considered similar if it starts with `name` (e.g. `docker-comp`
+/// for `docker-compose`), or if it can be reached with a few typos. Spaces,
+/// dashes and underscores are treated as the same character, and are ignored
+/// entirely as a second chance, so `gitcommit` matches `git-commit` as well.
+pub fn suggest_pages<'a>(name: &str, pages: &'a [String]) -> Vec<&'a str> {
+    let name = normalize(name);
+    let compact_name = compact(&name);
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    let mut matches: Vec<(usize, &str)> = pages
+        .iter()
+        .filter_map(|page| {
+            let normalized = normalize(page);
+            let distance = if normalized.starts_with(&name) {
+                0
+            } else {
+                edit_distance(&name, &normalized)
+                    .min(edit_distance(&compact_name, &compact(&normalized)))
+            };
+            (distance <= max_distance).then_some((distance, page.as_str()))
+        })
+        .collect();
+
+    // Prefer closer matches, then shorter page names
+    matches.sort_by_key(|&(distance, page)| (distance, page.len(), page));
+    matches
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, page)| page)
+        .collect()
+}
+
+/// Lowercase a page name and unify the word separators.
+fn normalize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            ' ' | '_' => '-',
+            c => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+/// Remove the word separators from a normalized page name.
+fn compact(name: &str) -> String {
+    name.chars().filter(|&c| c != '-').collect()
+}
+
+/// Return the number of single character insertions, deletions,
+/// substitutions and transpositions of adjacent characters needed to turn `a`
+/// into `b` (optimal string alignment distance).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // Rows of the distance matrix for the two previous and the current
+    // character of `a`
+    let mut before_previous: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before_previous[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before_previous, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| (*name).to_string()).collect()
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("tar", ""), 3);
+        assert_eq!(edit_distance("", "tar"), 3);
+        assert_eq!(edit_distance("tar", "tar"), 0);
+        assert_eq!(edit_distance("tar", "bar"), 1);
+        assert_eq!(edit_distance("tar", "star"), 1);
+        assert_eq!(edit_distance("compsoe", "compose"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_suggest_typos() {
+        let pages = pages(&["docker", "docker-compose", "docker-container", "git"]);
+        assert_eq!(suggest_pages("docker-compsoe", &pages), ["docker-compose"]);
+        assert_eq!(suggest_pages("dokcer", &pages), ["docker"]);
+        assert!(suggest_pages("xyz", &pages).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_prefixes() {
+        let pages = pages(&[
+            "git",
+            "git-commit",
+            "git-checkout",
+            "git-cherry-pick",
+            "gitk",
+        ]);
+        // Close matches come first
+        assert_eq!(
+            suggest_pages("git-ch", &pages),
+            ["git-checkout", "git-cherry-pick", "git"]
+        );
+        // At most three suggestions, shorter names first
+        assert_eq!(suggest_pages("gi", &pages), ["git", "gitk", "git-commit"]);
+    }
+
+    #[test]
+    fn test_suggest_separators() {
+        let pages = pages(&["git-commit", "git-log"]);
+        assert_eq!(suggest_pages("git commit", &pages), ["git-commit"]);
+        assert_eq!(suggest_pages("gitcommit", &pages), ["git-commit"]);
+        assert_eq!(suggest_pages("git_comit", &pages), ["git-commit"]);
+    }
+}
@@ -35,16 +35,7 @@ impl fmt::Display for PlatformType {
 
 impl clap::ValueEnum for PlatformType {
     fn value_variants<'a>() -> &'a [Self] {
-        &[
-            Self::Linux,
-            Self::OsX,
-            Self::SunOs,
-            Self::Windows,
-            Self::Android,
-            Self::FreeBsd,
-            Self::NetBsd,
-            Self::OpenBsd,
-        ]
+        Self::all()
     }
 
     fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
@@ -62,6 +53,20 @@ impl clap::ValueEnum for PlatformType {
 }
 
 impl PlatformType {
+    /// Return all platforms.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::Linux,
+            Self::OsX,
+            Self::SunOs,
+            Self::Windows,
+            Self::Android,
+            Self::FreeBsd,
+            Self::NetBsd,
+            Self::OpenBsd,
+        ]
+    }
+
     #[cfg(target_os = "linux")]
     pub fn current() -> Self {
         Self::Linux
@@ -112,18 +117,33 @@ impl PlatformType {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Deserialize, clap::ValueEnum)]
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone, Deserialize, clap::ValueEnum)]
 #[serde(rename_all = "lowercase")]
 pub enum ColorOptions {
     Always,
+    #[default]
     Auto,
     Never,
 }
 
-impl Default for ColorOptions {
-    fn default() -> Self {
-        Self::Auto
-    }
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Which form of option placeholders like `{{[-o|--output]}}` to show
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionStyle {
+    /// The short option, e.g. `-o`
+    Short,
+    /// The long option, e.g. `--output`
+    #[default]
+    Long,
+    /// Both options, e.g. `[-o|--output]`
+    Both,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use yansi::Color;
 
 /// Print a warning to stderr. If `enable_styles` is true, then a yellow
@@ -12,6 +14,42 @@ pub fn print_error(enable_styles: bool, error: &anyhow::Error) {
     print_msg(enable_styles, &format!("{error:?}"), "Error: ", Color::Red);
 }
 
+/// Format a duration in a human readable way, using the largest unit that
+/// fits (e.g. "3 days").
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (value, unit) = match secs {
+        0..=59 => (secs, "second"),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86_399 => (secs / 3600, "hour"),
+        _ => (secs / 86_400, "day"),
+    };
+    if value == 1 {
+        format!("{value} {unit}")
+    } else {
+        format!("{value} {unit}s")
+    }
+}
+
+/// Format a size in bytes using binary units (e.g. "1.5 MiB").
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    #[allow(clippy::cast_precision_loss)] // Only used for display
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next_unit in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next_unit;
+    }
+    format!("{size:.1} {unit}")
+}
+
 fn print_msg(enable_styles: bool, message: &str, prefix: &'static str, color: Color) {
     if enable_styles {
         eprintln!("{}{}", color.paint(prefix), color.paint(message));
@@ -19,3 +57,26 @@ fn print_msg(enable_styles: bool, message: &str, prefix: &'static str, color: Co
         eprintln!("{message}");
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(0)), "0 seconds");
+        assert_eq!(format_duration(Duration::from_secs(1)), "1 second");
+        assert_eq!(format_duration(Duration::from_secs(150)), "2 minutes");
+        assert_eq!(format_duration(Duration::from_secs(3600)), "1 hour");
+        assert_eq!(format_duration(Duration::from_secs(86_400 * 40)), "40 days");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1024), "1.0 KiB");
+        assert_eq!(format_size(1536 * 1024), "1.5 MiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
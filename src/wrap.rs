@@ -0,0 +1,213 @@
+//! Functions for wrapping lines to the width of the terminal.
+
+use std::ops::Range;
+
+use unicode_width::UnicodeWidthStr;
+
+/// Marks that a line of code is continued on the next line.
+pub const CONTINUATION_MARKER: &str = " \\";
+
+/// A line of text, given as pieces of text along with whether the piece may be
+/// broken up when wrapping.
+pub type Pieces<'a> = [(&'a str, bool)];
+
+/// A wrapped line, as a list of fragments, each of which is the index of a
+/// piece and the part of its text on that line.
+pub type Line<'a> = Vec<(usize, &'a str)>;
+
+/// Split a line of text into lines of at most `width` columns.
+///
+/// Lines are only broken at whitespace in breakable pieces, so that
+/// unbreakable pieces and the words around them are kept intact. Words that
+/// are wider than `width` (like long URLs) are put on a line of their own
+/// instead of being broken up. The continuation lines are assumed to be
+/// indented by `indent` columns, their leading whitespace is removed.
+pub fn wrap_text<'a>(pieces: &Pieces<'a>, width: usize, indent: usize) -> Vec<Line<'a>> {
+    // Fragments are tracked as byte ranges within their piece, so that
+    // adjacent fragments of the same piece can be merged
+    type Fragments = Vec<(usize, Range<usize>)>;
+    let fragments_width = |fragments: &Fragments| -> usize {
+        fragments
+            .iter()
+            .map(|(i, range)| pieces[*i].0[range.clone()].width())
+            .sum()
+    };
+
+    let mut lines: Vec<Fragments> = vec![Vec::new()];
+    let mut line_width = 0;
+    let mut line_has_words = false;
+    // The whitespace and the word that are about to be added to the line
+    let mut space = Vec::new();
+    let mut word = Vec::new();
+
+    let mut add_word = |space: &mut Fragments, word: &mut Fragments| {
+        if word.is_empty() {
+            return;
+        }
+        let mut space_width = fragments_width(space);
+        let word_width = fragments_width(word);
+        if line_has_words && line_width + space_width + word_width > width {
+            lines.push(Vec::new());
+            line_width = indent;
+            space.clear();
+            space_width = 0;
+        }
+        line_width += space_width + word_width;
+        let line = lines.last_mut().expect("there is always a line");
+        for (i, range) in space.drain(..).chain(word.drain(..)) {
+            match line.last_mut() {
+                Some((last, last_range)) if *last == i && last_range.end == range.start => {
+                    last_range.end = range.end;
+                }
+                _ => line.push((i, range)),
+            }
+        }
+        line_has_words = true;
+    };
+
+    for (i, &(text, breakable)) in pieces.iter().enumerate() {
+        if !breakable {
+            // Unbreakable pieces are part of the surrounding word
+            word.push((i, 0..text.len()));
+            continue;
+        }
+        for (is_space, range) in whitespace_runs(text) {
+            if is_space {
+                add_word(&mut space, &mut word);
+                space.push((i, range));
+            } else {
+                word.push((i, range));
+            }
+        }
+    }
+    add_word(&mut space, &mut word);
+
+    lines
+        .into_iter()
+        .map(|line| {
+            line.into_iter()
+                .map(|(i, range)| (i, &pieces[i].0[range]))
+                .collect()
+        })
+        .collect()
+}
+
+/// Split a line of code into lines of at most `width` columns like
+/// [`wrap_text`], leaving room for the [`CONTINUATION_MARKER`] if the code
+/// doesn't fit on one line.
+pub fn wrap_code<'a>(pieces: &Pieces<'a>, width: usize, indent: usize) -> Vec<Line<'a>> {
+    if pieces.iter().map(|(text, _)| text.width()).sum::<usize>() <= width {
+        return vec![pieces.iter().map(|&(text, _)| text).enumerate().collect()];
+    }
+    wrap_text(
+        pieces,
+        width.saturating_sub(CONTINUATION_MARKER.width()),
+        indent,
+    )
+}
+
+/// Split text into alternating runs of whitespace and other characters. Each
+/// run is returned as a byte range, along with whether it is whitespace.
+fn whitespace_runs(text: &str) -> impl Iterator<Item = (bool, Range<usize>)> + '_ {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        let is_space = text[start..].chars().next()?.is_whitespace();
+        let end = text[start..]
+            .find(|c: char| c.is_whitespace() != is_space)
+            .map_or(text.len(), |length| start + length);
+        let range = start..end;
+        start = end;
+        Some((is_space, range))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines<'a>(pieces: &Pieces<'a>, wrapped: &[Line<'a>]) -> Vec<String> {
+        wrapped
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|&(i, text)| {
+                        if pieces[i].1 {
+                            text.to_string()
+                        } else {
+                            format!("[{text}]")
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        let wrap = |text, width| lines(&[(text, true)], &wrap_text(&[(text, true)], width, 0));
+        assert_eq!(wrap("", 10), [""]);
+        assert_eq!(wrap("Archiving utility.", 20), ["Archiving utility."]);
+        assert_eq!(
+            wrap("Create an archive from files.", 12),
+            ["Create an", "archive from", "files."]
+        );
+        // Long words are not broken up
+        assert_eq!(
+            wrap("See <https://example.com/a/long/url>.", 10),
+            ["See", "<https://example.com/a/long/url>."]
+        );
+        // Umlauts take up one column each, even though they take up two bytes
+        assert_eq!(wrap("Müll heute größer", 10), ["Müll heute", "größer"]);
+        // CJK characters take up two columns each
+        assert_eq!(wrap("压缩 文件 归档", 10), ["压缩 文件", "归档"]);
+    }
+
+    #[test]
+    fn test_wrap_code() {
+        let pieces = [
+            ("  tar cf ", true),
+            ("target.tar", false),
+            (" ", true),
+            ("file1", false),
+            (" --exclude=", true),
+            ("path/to/the files", false),
+        ];
+        let wrap = |width| lines(&pieces, &wrap_code(&pieces, width, 4));
+        // Adjacent fragments of a piece are merged
+        assert_eq!(wrap_code(&pieces, 30, 4)[0][0], (0, "  tar cf "));
+        assert_eq!(
+            wrap(100),
+            ["  tar cf [target.tar] [file1] --exclude=[path/to/the files]"]
+        );
+        assert_eq!(
+            wrap(30),
+            [
+                "  tar cf [target.tar] [file1]",
+                "--exclude=[path/to/the files]"
+            ]
+        );
+        // Placeholders are not broken up, even if they don't fit
+        assert_eq!(
+            wrap(10),
+            [
+                "  tar cf",
+                "[target.tar]",
+                "[file1]",
+                "--exclude=[path/to/the files]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrap_code_i18n() {
+        let pieces = [("grep ", true), ("größer", false), (" höhlenrätsel", true)];
+        assert_eq!(
+            lines(&pieces, &wrap_code(&pieces, 24, 2)),
+            ["grep [größer] höhlenrätsel"]
+        );
+        assert_eq!(
+            lines(&pieces, &wrap_code(&pieces, 20, 2)),
+            ["grep [größer]", "höhlenrätsel"]
+        );
+    }
+}
@@ -1,9 +1,16 @@
 //! Integration tests.
 
 use std::{
-    fs::{create_dir_all, File},
-    io::Write,
+    fs::{self, create_dir_all, File},
+    io::{BufRead, BufReader, Cursor, Read, Write},
+    net::TcpListener,
+    path::{Path, PathBuf},
     process::Command,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
     time::{Duration, SystemTime},
 };
 
@@ -12,7 +19,11 @@ use predicates::{
     boolean::PredicateBooleanExt,
     prelude::predicate::str::{contains, diff, is_empty, is_match},
 };
+use reqwest::Url;
+use rustls::{ServerConfig, ServerConnection, StreamOwned};
+use sha2::{Digest, Sha256};
 use tempfile::{Builder as TempfileBuilder, TempDir};
+use zip::{write::SimpleFileOptions, ZipWriter};
 
 // TODO: Should be 'cache::CACHE_DIR_ENV_VAR'. This requires to have a library crate for the logic.
 static CACHE_DIR_ENV_VAR: &str = "TEALDEER_CACHE_DIR";
@@ -96,6 +107,61 @@ impl TestEnv {
         file.write_all(contents.as_bytes()).unwrap();
     }
 
+    /// Publish per-language archives containing the given pages (as
+    /// `(path, contents)` pairs, e.g. `("pages.de/common/ls.md", "...")`) in a
+    /// new directory under `input_dir`, along with their checksums file, and
+    /// return the path of that directory.
+    fn add_archive_source(&self, name: &str, pages: &[(&str, &str)]) -> PathBuf {
+        let dir = self.input_dir.path().join(name);
+        create_dir_all(&dir).unwrap();
+
+        let mut language_dirs: Vec<&str> = pages
+            .iter()
+            .map(|(path, _)| path.split_once('/').unwrap().0)
+            .collect();
+        language_dirs.sort_unstable();
+        language_dirs.dedup();
+
+        let mut checksums = String::new();
+        for language_dir in language_dirs {
+            let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+            for (path, contents) in pages {
+                let Some(path) = path
+                    .strip_prefix(language_dir)
+                    .and_then(|path| path.strip_prefix('/'))
+                else {
+                    continue;
+                };
+                writer
+                    .start_file(path, SimpleFileOptions::default())
+                    .unwrap();
+                writer.write_all(contents.as_bytes()).unwrap();
+            }
+            let archive = writer.finish().unwrap().into_inner();
+
+            let language = language_dir.strip_prefix("pages.").unwrap_or("en");
+            let file_name = format!("tldr-pages.{language}.zip");
+            write_file(&dir.join(&file_name), &archive);
+            checksums.push_str(&format!("{:x}  {file_name}\n", Sha256::digest(&archive)));
+        }
+        write_file(&dir.join("tldr.sha256sums"), checksums.as_bytes());
+
+        dir
+    }
+
+    /// Pretend that the cache was last updated at `time` by rewriting the
+    /// cache metadata.
+    fn set_last_update(&self, time: SystemTime) {
+        let path = self.cache_dir.path().join("metadata.toml");
+        let mut metadata: toml::Table = fs::read_to_string(&path).unwrap().parse().unwrap();
+        let updated_at = time.duration_since(SystemTime::UNIX_EPOCH).unwrap();
+        metadata.insert(
+            "updated_at".to_string(),
+            toml::Value::Integer(updated_at.as_secs().try_into().unwrap()),
+        );
+        write_file(&path, metadata.to_string().as_bytes());
+    }
+
     /// Disable default features.
     #[allow(dead_code)] // Might be useful in the future
     fn no_default_features(mut self) -> Self {
@@ -133,6 +199,178 @@ impl TestEnv {
     }
 }
 
+fn write_file(path: &Path, contents: &[u8]) {
+    let mut file = File::create(path).unwrap();
+    file.write_all(contents).unwrap();
+}
+
+/// Return the `file://` URL of a directory.
+fn file_url(dir: &Path) -> String {
+    Url::from_directory_path(dir).unwrap().to_string()
+}
+
+/// A minimal HTTP server that serves the files in a directory and records the
+/// head of every request it receives.
+///
+/// The SHA-256 checksum of a file is used as its ETag, and requests with a
+/// matching `If-None-Match` header are answered with "304 Not Modified".
+///
+/// The server can also act as a stand-in HTTP proxy. In that case, the host
+/// of the requested URL is ignored and the files are served all the same.
+struct TestHttpServer {
+    url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+    failures: Arc<AtomicUsize>,
+}
+
+impl TestHttpServer {
+    fn serve(dir: PathBuf) -> Self {
+        Self::start(dir, None)
+    }
+
+    /// Serve the files over HTTPS, using a certificate for `127.0.0.1` that
+    /// is signed by the CA in `tests/ca.pem`.
+    fn serve_https(dir: PathBuf) -> Self {
+        let certificates = rustls_pemfile::certs(&mut include_bytes!("server.pem").as_slice())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let key = rustls_pemfile::private_key(&mut include_bytes!("server.key").as_slice())
+            .unwrap()
+            .unwrap();
+        let config =
+            ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_no_client_auth()
+                .with_single_cert(certificates, key)
+                .unwrap();
+        Self::start(dir, Some(Arc::new(config)))
+    }
+
+    fn start(dir: PathBuf, tls_config: Option<Arc<ServerConfig>>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let scheme = if tls_config.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+        let url = format!("{scheme}://{}/", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let failures = Arc::new(AtomicUsize::new(0));
+
+        let recorded_requests = Arc::clone(&requests);
+        let remaining_failures = Arc::clone(&failures);
+        thread::spawn(move || {
+            for stream in listener.incoming().filter_map(Result::ok) {
+                match tls_config {
+                    Some(ref config) => {
+                        let connection = ServerConnection::new(Arc::clone(config)).unwrap();
+                        Self::respond(
+                            StreamOwned::new(connection, stream),
+                            &dir,
+                            &recorded_requests,
+                            &remaining_failures,
+                        );
+                    }
+                    None => Self::respond(stream, &dir, &recorded_requests, &remaining_failures),
+                }
+            }
+        });
+
+        Self {
+            url,
+            requests,
+            failures,
+        }
+    }
+
+    /// Answer a single request.
+    fn respond(
+        mut stream: impl Read + Write,
+        dir: &Path,
+        recorded_requests: &Mutex<Vec<String>>,
+        remaining_failures: &AtomicUsize,
+    ) {
+        // Read the request line and headers
+        let mut request = String::new();
+        {
+            let mut reader = BufReader::new(&mut stream);
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                request.push_str(&line);
+            }
+        }
+        recorded_requests.lock().unwrap().push(request.clone());
+
+        // Requests sent to a proxy contain the absolute URL
+        let target = request.split_whitespace().nth(1).unwrap_or("/");
+        let path = target.strip_prefix("http://").map_or(target, |url| {
+            url.find('/').map_or("/", |path_start| &url[path_start..])
+        });
+        let if_none_match = request.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("if-none-match")
+                .then(|| value.trim().to_string())
+        });
+        let fail = remaining_failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        let response = match fs::read(dir.join(path.trim_start_matches('/'))) {
+            _ if fail => b"HTTP/1.1 503 Service Unavailable\r\n\
+                           Content-Length: 0\r\n\
+                           Connection: close\r\n\r\n"
+                .to_vec(),
+            Ok(contents) => {
+                let etag = format!("\"{:x}\"", Sha256::digest(&contents));
+                if if_none_match.as_ref() == Some(&etag) {
+                    format!(
+                        "HTTP/1.1 304 Not Modified\r\n\
+                         ETag: {etag}\r\n\
+                         Connection: close\r\n\r\n"
+                    )
+                    .into_bytes()
+                } else {
+                    let mut response = format!(
+                        "HTTP/1.1 200 OK\r\n\
+                         ETag: {etag}\r\n\
+                         Content-Length: {}\r\n\
+                         Connection: close\r\n\r\n",
+                        contents.len()
+                    )
+                    .into_bytes();
+                    response.extend(contents);
+                    response
+                }
+            }
+            Err(_) => b"HTTP/1.1 404 Not Found\r\n\
+                        Content-Length: 0\r\n\
+                        Connection: close\r\n\r\n"
+                .to_vec(),
+        };
+        let _ = stream.write_all(&response).and_then(|()| stream.flush());
+    }
+
+    /// Answer the next `count` requests with "503 Service Unavailable".
+    fn fail_next_requests(&self, count: usize) {
+        self.failures.store(count, Ordering::SeqCst);
+    }
+
+    /// Return the heads of all requests for the given path.
+    fn requests_for(&self, path: &str) -> Vec<String> {
+        let request_line = format!("GET {path} ");
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.starts_with(&request_line))
+            .cloned()
+            .collect()
+    }
+}
+
 #[test]
 fn test_missing_cache() {
     TestEnv::new()
@@ -164,6 +402,598 @@ fn test_update_cache() {
     testenv.command().args(["sl"]).assert().success();
 }
 
+#[test]
+fn test_update_cache_from_file_source() {
+    let testenv = TestEnv::new();
+    let source = file_url(&testenv.add_archive_source("mirror", &[("pages/common/sl.md", "# sl")]));
+
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &source])
+        .assert()
+        .success()
+        .stderr(contains("Successfully updated cache."));
+
+    testenv.command().args(["sl"]).assert().success();
+
+    testenv
+        .command()
+        .args(["--show-paths"])
+        .assert()
+        .success()
+        .stdout(contains(format!("Pages source:     {source} (updated ")));
+}
+
+#[test]
+fn test_cache_info() {
+    let testenv = TestEnv::new();
+    let source = file_url(&testenv.add_archive_source(
+        "mirror",
+        &[
+            ("pages/common/sl.md", "# sl"),
+            ("pages/common/ls.md", "# ls"),
+            ("pages/linux/ip.md", "# ip"),
+            ("pages.de/common/sl.md", "# sl"),
+        ],
+    ));
+    testenv.write_config("[updates]\nlanguages = ['en', 'de']");
+
+    testenv
+        .command()
+        .args(["--cache-info"])
+        .assert()
+        .success()
+        .stdout(contains(format!(
+            "Cache dir:        {}\n",
+            testenv.cache_dir.path().display()
+        )))
+        .stdout(contains("Archive sources:  https://"))
+        .stdout(contains("Pages:            not present"));
+    testenv
+        .command()
+        .args(["--cache-info", "--archive-source", &source])
+        .assert()
+        .success()
+        .stdout(contains(format!("Archive sources:  {source}\n")));
+
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &source])
+        .assert()
+        .success();
+
+    testenv
+        .command()
+        .args(["--cache-info"])
+        .assert()
+        .success()
+        .stdout(contains(format!("Pages source:     {source}\n")))
+        .stdout(contains(format!(
+            "Updated by:       tealdeer {}\n",
+            env!("CARGO_PKG_VERSION")
+        )))
+        .stdout(contains("Old cache layout: not present\n"))
+        .stdout(contains(
+            "  de: 1\n    common            1\n  en: 3\n    common            2\n    linux             1\n",
+        ));
+}
+
+#[test]
+fn test_page_index() {
+    let testenv = TestEnv::new();
+    let source = file_url(&testenv.add_archive_source(
+        "mirror",
+        &[
+            ("pages/common/sl.md", "# sl\n\n> Steam locomotive.\n"),
+            ("pages/linux/ip.md", "# ip\n\n> Show network interfaces.\n"),
+        ],
+    ));
+    let index_path = testenv.cache_dir.path().join("pages.index");
+
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &source])
+        .assert()
+        .success();
+    assert!(index_path.is_file());
+
+    // Pages added after the update are picked up by rebuilding the index
+    testenv.add_entry("ls", "# ls\n\n> List directory contents.\n");
+    testenv
+        .command()
+        .args(["--list", "--platform", "linux"])
+        .assert()
+        .success()
+        .stdout("ip\nls\nsl\n");
+    testenv
+        .command()
+        .args(["--search", "directory"])
+        .assert()
+        .success()
+        .stdout(contains("ls"));
+
+    // A missing index is rebuilt as well, but not just to look up a page
+    std::fs::remove_file(&index_path).unwrap();
+    testenv.command().args(["ip"]).assert().success();
+    assert!(!index_path.exists());
+    testenv.command().args(["--list"]).assert().success();
+    assert!(index_path.is_file());
+
+    testenv.command().args(["--clear-cache"]).assert().success();
+    assert!(!index_path.exists());
+}
+
+#[test]
+fn test_update_cache_tries_archive_sources_in_order() {
+    let testenv = TestEnv::new();
+    let missing = file_url(&testenv.input_dir.path().join("missing"));
+    let source = file_url(&testenv.add_archive_source("mirror", &[("pages/common/sl.md", "# sl")]));
+    testenv.write_config(format!(
+        "[updates]\narchive_source = ['{missing}', '{source}']"
+    ));
+
+    testenv
+        .command()
+        .args(["--update"])
+        .assert()
+        .success()
+        .stderr(contains(format!("Could not update from {missing}")))
+        .stderr(contains("Successfully updated cache."));
+
+    testenv.command().args(["sl"]).assert().success();
+
+    // Falling back to the next source is not announced with `--quiet`
+    testenv
+        .command()
+        .args(["--update", "--quiet"])
+        .assert()
+        .success()
+        .stderr(contains("Could not update from").not());
+
+    // If no source works, all errors are reported
+    testenv
+        .command()
+        .args([
+            "--update",
+            "--archive-source",
+            &missing,
+            "--archive-source",
+            &missing,
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("None of the archive sources could be used"));
+}
+
+#[test]
+fn test_update_cache_checksum_mismatch() {
+    let testenv = TestEnv::new();
+    testenv.add_entry("sl", "# sl");
+
+    let source_dir = testenv.add_archive_source("mirror", &[("pages/common/ls.md", "# ls")]);
+    write_file(
+        &source_dir.join("tldr.sha256sums"),
+        format!("{}  tldr-pages.en.zip\n", "0".repeat(64)).as_bytes(),
+    );
+    let source = file_url(&source_dir);
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &source])
+        .assert()
+        .failure()
+        .stderr(contains("Checksum mismatch for tldr-pages.en.zip"));
+
+    // The existing cache must not have been touched
+    testenv.command().args(["sl"]).assert().success();
+    testenv.command().args(["ls"]).assert().failure();
+}
+
+#[test]
+fn test_update_cache_languages() {
+    let testenv = TestEnv::new();
+    let source = file_url(&testenv.add_archive_source(
+        "mirror",
+        &[
+            ("pages/common/sl.md", "# sl"),
+            ("pages.de/common/sl.md", "# sl\n\n> Deutsch"),
+            ("pages.fr/common/sl.md", "# sl (fr)"),
+        ],
+    ));
+    let pages_dir = testenv.cache_dir.path().join(TLDR_PAGES_DIR);
+
+    // By default, the languages are derived from the environment. Languages
+    // without translations are skipped.
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &source])
+        .env("LANG", "fr_FR.UTF-8")
+        .env("LANGUAGE", "it")
+        .assert()
+        .success();
+    assert!(pages_dir.join("pages").is_dir());
+    assert!(pages_dir.join("pages.fr").is_dir());
+    assert!(!pages_dir.join("pages.de").exists());
+
+    // The languages can be configured explicitly
+    testenv.write_config("[updates]\nlanguages = ['en', 'de']");
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &source])
+        .env("LANG", "fr_FR.UTF-8")
+        .assert()
+        .success();
+    assert!(pages_dir.join("pages").is_dir());
+    assert!(pages_dir.join("pages.de").is_dir());
+    assert!(!pages_dir.join("pages.fr").exists());
+
+    testenv
+        .command()
+        .args(["sl", "--language", "de", "--color", "never"])
+        .assert()
+        .success()
+        .stdout(contains("Deutsch"));
+
+    // If none of the languages are available, the update fails
+    testenv.write_config("[updates]\nlanguages = ['it']");
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &source])
+        .assert()
+        .failure()
+        .stderr(contains("No pages available for any of the languages it"));
+}
+
+#[test]
+fn test_update_cache_not_modified() {
+    let testenv = TestEnv::new();
+    let server = TestHttpServer::serve(
+        testenv.add_archive_source("mirror", &[("pages/common/sl.md", "# sl")]),
+    );
+
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &server.url])
+        .assert()
+        .success()
+        .stderr(contains("Successfully updated cache."));
+    assert_eq!(server.requests_for("/tldr-pages.en.zip").len(), 1);
+
+    // Pretend that the cache is outdated
+    testenv.set_last_update(SystemTime::UNIX_EPOCH);
+
+    // The second update sends the ETag of the first download. As the archive
+    // hasn't changed, nothing but the update time is touched.
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &server.url])
+        .assert()
+        .success()
+        .stderr(contains("Successfully updated cache."));
+    let archive_requests = server.requests_for("/tldr-pages.en.zip");
+    assert_eq!(archive_requests.len(), 2);
+    assert!(archive_requests[1]
+        .to_lowercase()
+        .contains("if-none-match: \""));
+
+    testenv
+        .command()
+        .args(["sl"])
+        .assert()
+        .success()
+        .stderr(contains("The cache hasn't been updated").not());
+}
+
+#[test]
+fn test_update_cache_partially_modified() {
+    let testenv = TestEnv::new();
+    let mirror = testenv.add_archive_source(
+        "mirror",
+        &[
+            ("pages/common/sl.md", "# sl"),
+            ("pages.de/common/sl.md", "# sl"),
+        ],
+    );
+    let server = TestHttpServer::serve(mirror.clone());
+    testenv.write_config("[updates]\nlanguages = ['en', 'de']");
+
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &server.url])
+        .assert()
+        .success();
+
+    // Only the German archive changes. The English one is then downloaded
+    // again, since the pages directory is replaced as a whole.
+    let changed = testenv.add_archive_source("changed", &[("pages.de/common/ls.md", "# ls")]);
+    fs::copy(
+        changed.join("tldr-pages.de.zip"),
+        mirror.join("tldr-pages.de.zip"),
+    )
+    .unwrap();
+    let checksums = fs::read_to_string(mirror.join("tldr.sha256sums")).unwrap();
+    let de_checksum = fs::read_to_string(changed.join("tldr.sha256sums")).unwrap();
+    let en_checksum = checksums
+        .lines()
+        .find(|line| line.ends_with("tldr-pages.en.zip"))
+        .unwrap();
+    write_file(
+        &mirror.join("tldr.sha256sums"),
+        format!("{en_checksum}\n{de_checksum}").as_bytes(),
+    );
+    testenv.set_last_update(SystemTime::UNIX_EPOCH);
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &server.url])
+        .assert()
+        .success();
+    assert_eq!(server.requests_for("/tldr-pages.en.zip").len(), 3);
+
+    // The validators of the repeated download are kept, so the next update
+    // only asks whether the archives have changed
+    testenv.set_last_update(SystemTime::UNIX_EPOCH);
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &server.url])
+        .assert()
+        .success();
+    for archive in ["/tldr-pages.en.zip", "/tldr-pages.de.zip"] {
+        let requests = server.requests_for(archive);
+        assert!(requests
+            .last()
+            .unwrap()
+            .to_lowercase()
+            .contains("if-none-match: \""));
+    }
+    testenv
+        .command()
+        .args(["--language", "de", "ls"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_update_cache_retries_failed_downloads() {
+    let testenv = TestEnv::new();
+    let server = TestHttpServer::serve(
+        testenv.add_archive_source("mirror", &[("pages/common/sl.md", "# sl")]),
+    );
+    testenv.write_config("[updates]\ndownload_retries = 1");
+
+    server.fail_next_requests(1);
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &server.url])
+        .assert()
+        .success()
+        .stderr(contains("503 Service Unavailable"))
+        .stderr(contains("Retrying in 0.5s (1/1)."))
+        .stderr(contains("Successfully updated cache."));
+    assert_eq!(server.requests_for("/tldr.sha256sums").len(), 2);
+
+    // Retries are not announced in quiet mode, and give up eventually
+    server.fail_next_requests(2);
+    testenv
+        .command()
+        .args(["--update", "--quiet", "--archive-source", &server.url])
+        .assert()
+        .failure()
+        .stderr(contains("Retrying").not())
+        .stderr(contains("503 Service Unavailable"));
+    assert_eq!(server.requests_for("/tldr.sha256sums").len(), 4);
+}
+
+#[test]
+fn test_update_cache_timeout() {
+    let testenv = TestEnv::new();
+    testenv.write_config("[updates]\nread_timeout_secs = 1\ndownload_retries = 0");
+
+    // A server that accepts connections, but never responds
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let _streams: Vec<_> = listener.incoming().collect();
+    });
+
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &url])
+        .assert()
+        .failure()
+        .stderr(contains("Could not download tldr pages from"))
+        .stderr(contains("timed out"));
+}
+
+#[test]
+fn test_update_cache_via_proxy() {
+    let testenv = TestEnv::new();
+    let proxy = TestHttpServer::serve(
+        testenv.add_archive_source("mirror", &[("pages/common/sl.md", "# sl")]),
+    );
+    let proxy_with_credentials = proxy.url.replace("http://", "http://user:secret@");
+
+    // The host of the archive source is only resolved by the proxy
+    testenv
+        .command()
+        .args(["--update", "--archive-source", "http://tldr.invalid/"])
+        .env("http_proxy", &proxy_with_credentials)
+        .env("HTTP_PROXY", "ftp://ignored")
+        .assert()
+        .success()
+        .stderr(contains("Successfully updated cache."));
+    let requests = proxy.requests_for("http://tldr.invalid/tldr.sha256sums");
+    assert_eq!(requests.len(), 1);
+    // "user:secret" in Base64
+    assert!(requests[0]
+        .to_lowercase()
+        .contains("proxy-authorization: basic dxnlcjpzzwnyzxq="));
+
+    // A proxy in the config file overrides the environment
+    testenv.write_config(format!("[updates]\nproxy = '{}'", proxy.url));
+    testenv
+        .command()
+        .args(["--update", "--archive-source", "http://tldr.invalid/"])
+        .env("ALL_PROXY", "http://127.0.0.1:1")
+        .assert()
+        .success();
+    assert_eq!(
+        proxy
+            .requests_for("http://tldr.invalid/tldr.sha256sums")
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_update_cache_no_proxy() {
+    let testenv = TestEnv::new();
+    let server = TestHttpServer::serve(
+        testenv.add_archive_source("mirror", &[("pages/common/sl.md", "# sl")]),
+    );
+    testenv.write_config("[updates]\ndownload_retries = 0");
+
+    // Nothing listens on port 1, so this only works if the proxy is bypassed
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &server.url])
+        .env("ALL_PROXY", "http://127.0.0.1:1")
+        .env("NO_PROXY", "localhost, 127.0.0.1")
+        .assert()
+        .success();
+    assert_eq!(server.requests_for("/tldr.sha256sums").len(), 1);
+}
+
+#[test]
+fn test_update_cache_invalid_proxy() {
+    let testenv = TestEnv::new();
+    let source = file_url(&testenv.add_archive_source("mirror", &[("pages/common/sl.md", "# sl")]));
+
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &source])
+        .env("HTTPS_PROXY", "socks5://proxy.example.com")
+        .assert()
+        .success();
+
+    // Unsupported proxies from the environment are skipped with a warning
+    testenv
+        .command()
+        .args([
+            "--update",
+            "--archive-source",
+            &source,
+            "--archive-source",
+            "https://unreachable.invalid/tldr",
+        ])
+        .env("HTTPS_PROXY", "socks5://proxy.example.com")
+        .assert()
+        .success()
+        .stderr(contains(
+            "Ignoring the HTTPS_PROXY environment variable: Unsupported proxy scheme `socks5`",
+        ));
+
+    // Malformed proxies from the environment are an error, even with `--quiet`
+    testenv
+        .command()
+        .args([
+            "--update",
+            "--quiet",
+            "--archive-source",
+            &source,
+            "--archive-source",
+            "https://unreachable.invalid/tldr",
+        ])
+        .env("HTTP_PROXY", "http://[::1")
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Invalid value of the HTTP_PROXY environment variable",
+        ));
+
+    // An invalid proxy in the config file is an error, as soon as it would
+    // be used for a source
+    testenv.write_config("[updates]\nproxy = 'http://[::1'");
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &source])
+        .assert()
+        .success();
+    testenv
+        .command()
+        .args([
+            "--update",
+            "--archive-source",
+            &source,
+            "--archive-source",
+            "https://unreachable.invalid/tldr",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Invalid value of the `proxy` option in the config file",
+        ));
+}
+
+#[test]
+fn test_update_cache_ca_bundle() {
+    let testenv = TestEnv::new();
+    let source = file_url(&testenv.add_archive_source("mirror", &[("pages/common/sl.md", "# sl")]));
+    let ca_bundle = testenv.input_dir.path().join("ca.pem");
+    write_file(&ca_bundle, include_bytes!("ca.pem"));
+    let invalid_ca_bundle = testenv.input_dir.path().join("invalid.pem");
+    write_file(&invalid_ca_bundle, b"not a certificate");
+
+    // The certificate of the server is only trusted with the CA bundle
+    let server = TestHttpServer::serve_https(
+        testenv.add_archive_source("https", &[("pages/common/ls.md", "# ls")]),
+    );
+    testenv.write_config("[updates]\ndownload_retries = 0");
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &server.url])
+        .assert()
+        .failure()
+        .stderr(contains("certificate"));
+
+    testenv.write_config(format!(
+        "[updates]\ndownload_retries = 0\nca_bundle = '{}'",
+        ca_bundle.display()
+    ));
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &server.url])
+        .assert()
+        .success();
+    testenv.command().args(["ls"]).assert().success();
+
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &source])
+        .assert()
+        .success();
+
+    // The environment variable overrides the config file
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &source])
+        .env("TEALDEER_CA_BUNDLE", &invalid_ca_bundle)
+        .assert()
+        .failure()
+        .stderr(contains(format!(
+            "The CA bundle at {} does not contain any PEM encoded certificates",
+            invalid_ca_bundle.display()
+        )));
+
+    testenv.write_config("[updates]\nca_bundle = '/nonexistent/ca.pem'");
+    testenv
+        .command()
+        .args(["--update", "--archive-source", &source])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Could not read the CA bundle at /nonexistent/ca.pem",
+        ));
+}
+
 #[test]
 fn test_quiet_cache() {
     let testenv = TestEnv::new();
@@ -212,11 +1042,7 @@ fn test_quiet_old_cache() {
         .success()
         .stdout(is_empty());
 
-    filetime::set_file_mtime(
-        testenv.cache_dir.path().join(TLDR_PAGES_DIR),
-        filetime::FileTime::from_unix_time(1, 0),
-    )
-    .unwrap();
+    testenv.set_last_update(SystemTime::UNIX_EPOCH);
 
     testenv
         .command()
@@ -617,14 +1443,179 @@ fn test_multiple_platform_command_search_not_found() {
     testenv.add_os_entry(
         "windows",
         "windows-only",
-        "this command only exists for Windows",
+        "this command only exists for Windows",
+    );
+
+    testenv
+        .command()
+        .args(["--platform", "macos", "--platform", "linux", "missing"])
+        .assert()
+        .failure()
+        .stderr(contains("Page `missing` not found in cache."));
+}
+
+#[test]
+fn test_page_not_found_suggestions() {
+    let testenv = TestEnv::new();
+    testenv.add_entry("docker-compose", "");
+    testenv.add_entry("docker", "");
+    testenv.add_os_entry("windows", "docker-desktop", "");
+
+    testenv
+        .command()
+        .args(["--platform", "linux", "docker-compsoe"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Page `docker-compsoe` not found in cache.\n\
+             Did you mean `docker-compose`?\n",
+        ));
+
+    // Pages of other platforms are suggested as well
+    testenv
+        .command()
+        .args(["--platform", "linux", "docker", "desk"])
+        .assert()
+        .failure()
+        .stderr(contains("Did you mean `docker-desktop`?"));
+
+    testenv
+        .command()
+        .args(["unrelated"])
+        .assert()
+        .failure()
+        .stderr(contains("Did you mean").not());
+}
+
+#[test]
+fn test_search() {
+    let testenv = TestEnv::new();
+    testenv.write_config(format!(
+        "[directories]\ncustom_pages_dir = '{}'",
+        testenv.custom_pages_dir.path().to_str().unwrap()
+    ));
+    testenv.add_entry(
+        "tar",
+        "# tar\n\n> Archiving utility.\n\n\
+         - Extract a (compressed) archive file into the current directory:\n\n\
+         `tar xf {{source.tar}}`\n",
+    );
+    testenv.add_entry(
+        "unzip",
+        "# unzip\n\n> Extract files/directories from ZIP archives.\n",
+    );
+    testenv.add_entry("ls", "# ls\n\n> List directory contents.\n");
+    testenv.add_os_entry(
+        "windows",
+        "expand",
+        "# expand\n\n> Extract files from a Windows archive.\n",
+    );
+    testenv.add_page_entry(
+        "7z",
+        "# 7z\n\n> File archiver.\n\n- Extract an existing archive:\n\n`7z x {{path}}`\n",
+    );
+
+    testenv
+        .command()
+        .args(["--platform", "linux", "--search", "extract archive"])
+        .assert()
+        .success()
+        .stdout(
+            "unzip  Extract files/directories from ZIP archives.\n\
+             7z     Extract an existing archive:\n\
+             tar    Extract a (compressed) archive file into the current directory:\n",
+        );
+
+    testenv
+        .command()
+        .args(["--search", "nonexistent"])
+        .assert()
+        .failure()
+        .stderr(contains("No pages found for `nonexistent`."));
+}
+
+#[test]
+fn test_follow_aliases() {
+    let testenv = TestEnv::new();
+    testenv.add_entry(
+        "tar",
+        "# tar\n\n> This command is an alias of `gnu-tar`.\n\n\
+         - View documentation for the original command:\n\n`tldr gnu-tar`\n",
+    );
+    testenv.add_entry("gnu-tar", "# gnu-tar\n\n> Archiving utility.\n");
+    testenv.add_entry("ll", "# ll\n\n> This command is an alias of `ls -l`.\n");
+
+    // Alias pages are shown as they are by default
+    testenv
+        .command()
+        .args(["--color", "never", "tar"])
+        .assert()
+        .success()
+        .stdout(contains("This command is an alias of `gnu-tar`."))
+        .stdout(contains("Archiving utility.").not());
+
+    testenv
+        .command()
+        .args(["--color", "never", "--follow-aliases", "tar"])
+        .assert()
+        .success()
+        .stdout("\n  `tar` is an alias of `gnu-tar`.\n\n  Archiving utility.\n\n");
+
+    testenv.write_config("[display]\nfollow_aliases = true\ncompact = true");
+    testenv
+        .command()
+        .args(["--color", "never", "tar"])
+        .assert()
+        .success()
+        .stdout("  `tar` is an alias of `gnu-tar`.\n  Archiving utility.\n\n");
+
+    // The alias page is shown if the original page doesn't exist
+    testenv
+        .command()
+        .args(["--color", "never", "ll"])
+        .assert()
+        .success()
+        .stdout(contains("This command is an alias of `ls -l`."));
+}
+
+#[test]
+fn test_multiple_platform_command_search_fallback() {
+    let testenv = TestEnv::new();
+    testenv.add_os_entry(
+        "windows",
+        "windows-only",
+        "# windows-only\n\n> this command only exists for Windows",
+    );
+    testenv.add_entry(
+        "common-page",
+        "# common-page\n\n> this command exists everywhere",
     );
 
+    // Pages of other platforms are used as a last resort
     testenv
         .command()
         .args(["--platform", "macos", "--platform", "linux", "windows-only"])
         .assert()
-        .stderr(contains("Page `windows-only` not found in cache."));
+        .success()
+        .stdout(contains("this command only exists for Windows"))
+        .stderr(contains(
+            "The page is not available for the requested platform, \
+             showing the page from Windows instead.",
+        ));
+
+    // No notice is shown for pages of the requested or the common platform
+    testenv
+        .command()
+        .args(["--platform", "windows", "windows-only"])
+        .assert()
+        .success()
+        .stderr(contains("showing the page from").not());
+    testenv
+        .command()
+        .args(["--platform", "linux", "common-page"])
+        .assert()
+        .success()
+        .stderr(contains("showing the page from").not());
 }
 
 #[test]
@@ -742,6 +1733,188 @@ fn test_multi_platform_list_flag_rendering() {
         .stdout("common\ndel\ndir\nls\nrm\nwinux\n");
 }
 
+#[test]
+fn test_list_long() {
+    let testenv = TestEnv::new();
+    testenv.write_config(format!(
+        "[directories]\ncustom_pages_dir = '{}'",
+        testenv.custom_pages_dir.path().to_str().unwrap()
+    ));
+
+    testenv.add_entry(
+        "tar",
+        "# tar\n\n> Archiving utility.\n> More information: <https://example.com>.\n",
+    );
+    testenv.add_os_entry("linux", "winux", "# winux\n\n> Linux version.\n");
+    testenv.add_os_entry("windows", "winux", "# winux\n\n> Windows version.\n");
+    testenv.add_os_entry("windows", "dir", "# dir\n\n> List a directory.\n");
+
+    testenv
+        .command()
+        .args([
+            "--color",
+            "never",
+            "--platform",
+            "linux",
+            "--list",
+            "--long",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "tar    common  Archiving utility.\n\
+             winux  linux   Linux version.\n",
+        );
+
+    testenv
+        .command()
+        .args([
+            "--color",
+            "never",
+            "--platform",
+            "windows",
+            "--list",
+            "--all-platforms",
+            "--long",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "dir    windows        List a directory.\n\
+             tar    common         Archiving utility.\n\
+             winux  linux,windows  Linux version.\n",
+        );
+
+    testenv
+        .command()
+        .args(["--platform", "linux", "--list", "--all-platforms"])
+        .assert()
+        .success()
+        .stdout("dir\ntar\nwinux\n");
+
+    testenv.add_page_entry("foo", "# foo\n\n> A custom page.\n");
+    testenv.add_patch_entry("tar", "- Extract an archive:\n\n`tar xf {{archive}}`\n");
+    testenv
+        .command()
+        .args([
+            "--color",
+            "never",
+            "--platform",
+            "linux",
+            "--list",
+            "--long",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "foo            custom   A custom page.\n\
+             tar    common  patched  Archiving utility.\n\
+             winux  linux            Linux version.\n",
+        );
+}
+
+#[test]
+fn test_list_long_wide_characters() {
+    let testenv = TestEnv::new();
+    testenv.write_config(format!(
+        "[directories]\ncustom_pages_dir = '{}'",
+        testenv.custom_pages_dir.path().to_str().unwrap()
+    ));
+    testenv.add_entry("tar", "# tar\n\n> Archiving utility.\n");
+    // Each of the CJK characters takes up two columns
+    testenv.add_page_entry("压缩", "# 压缩\n\n> Compress files.\n");
+
+    testenv
+        .command()
+        .args(["--color", "never", "--list", "--long"])
+        .assert()
+        .success()
+        .stdout(
+            "tar   common          Archiving utility.\n\
+             压缩          custom  Compress files.\n",
+        );
+}
+
+#[test]
+fn test_json_format() {
+    let testenv = TestEnv::new();
+    testenv.write_config(format!(
+        "[directories]\ncustom_pages_dir = '{}'",
+        testenv.custom_pages_dir.path().to_str().unwrap()
+    ));
+    testenv.add_os_entry(
+        "linux",
+        "tar",
+        "# tar\n\n> Archiving utility.\n\n- Extract an archive:\n\n`tar xf {{source.tar}}`\n",
+    );
+    testenv.add_page_entry("foo", "# foo\n\n> A custom page.\n");
+
+    let page_path = testenv
+        .cache_dir
+        .path()
+        .join(TLDR_PAGES_DIR)
+        .join("pages/linux/tar.md");
+    let output = testenv
+        .command()
+        .args(["--platform", "linux", "--format", "json", "tar"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let page: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        page,
+        serde_json::json!({
+            "title": "tar",
+            "description": ["Archiving utility."],
+            "more_info_url": null,
+            "examples": [{
+                "text": "Extract an archive:",
+                "code": [
+                    {"type": "literal", "text": "tar xf "},
+                    {"type": "placeholder", "text": "source.tar"},
+                ],
+            }],
+            "source_path": page_path,
+            "patch_path": null,
+            "platform": "linux",
+        })
+    );
+
+    let output = testenv
+        .command()
+        .args(["--platform", "linux", "--format", "json", "--list"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let list: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        list,
+        serde_json::json!([
+            {
+                "name": "foo",
+                "platforms": [],
+                "custom": true,
+                "patched": false,
+                "description": "A custom page.",
+            },
+            {
+                "name": "tar",
+                "platforms": ["linux"],
+                "custom": false,
+                "patched": false,
+                "description": "Archiving utility.",
+            },
+        ])
+    );
+
+    testenv
+        .command()
+        .args(["--format", "json", "--raw", "tar"])
+        .assert()
+        .failure()
+        .stderr(contains("cannot be used with"));
+}
+
 #[test]
 fn test_autoupdate_cache() {
     let testenv = TestEnv::new();
@@ -755,7 +1928,6 @@ fn test_autoupdate_cache() {
         .stderr(contains("Page cache not found. Please run `tldr --update`"));
 
     let config_file_path = testenv.config_dir.path().join("config.toml");
-    let cache_file_path = testenv.cache_dir.path().join(TLDR_PAGES_DIR);
 
     // Activate automatic updates, set the auto-update interval to 24 hours
     let mut config_file = File::create(config_file_path).unwrap();
@@ -782,16 +1954,14 @@ fn test_autoupdate_cache() {
     // The cache is not updated with a subsequent call
     check_cache_updated(false);
 
-    // We update the modification and access times such that they are about 23 hours from now.
+    // Pretend that the last update happened about 23 hours ago.
     // auto-update interval is 24 hours, the cache should not be updated
-    let new_mtime = SystemTime::now() - Duration::from_secs(82_800);
-    filetime::set_file_mtime(&cache_file_path, new_mtime.into()).unwrap();
+    testenv.set_last_update(SystemTime::now() - Duration::from_secs(82_800));
     check_cache_updated(false);
 
-    // We update the modification and access times such that they are about 25 hours from now.
+    // Pretend that the last update happened about 25 hours ago.
     // auto-update interval is 24 hours, the cache should be updated
-    let new_mtime = SystemTime::now() - Duration::from_secs(90_000);
-    filetime::set_file_mtime(&cache_file_path, new_mtime.into()).unwrap();
+    testenv.set_last_update(SystemTime::now() - Duration::from_secs(90_000));
     check_cache_updated(true);
 
     // The cache is not updated with a subsequent call
@@ -959,3 +2129,302 @@ fn test_raw_render_file() {
         .success()
         .stdout(diff(include_str!("inkscape-v1.md")));
 }
+
+#[test]
+fn test_example_selection() {
+    let testenv = TestEnv::new();
+    testenv.add_entry(
+        "tar",
+        "# tar\n\n> Archiving utility.\n\n\
+         - Create an archive from files:\n\n`tar cf {{target.tar}} {{file1}}`\n\n\
+         - Extract an archive:\n\n`tar xf {{source.tar}}`\n",
+    );
+
+    testenv
+        .command()
+        .args(["--color", "never", "tar", "--example", "2"])
+        .assert()
+        .success()
+        .stdout("\n  Extract an archive:\n\n      tar xf source.tar\n\n");
+
+    testenv
+        .command()
+        .args(["--color", "never", "tar", "--example", "2", "--numbered"])
+        .assert()
+        .success()
+        .stdout("\n  2. Extract an archive:\n\n      tar xf source.tar\n\n");
+
+    testenv
+        .command()
+        .args(["--color", "never", "tar", "--numbered"])
+        .assert()
+        .success()
+        .stdout(
+            "\n  Archiving utility.\n\n\
+             \x20 1. Create an archive from files:\n\n      tar cf target.tar file1\n\n\
+             \x20 2. Extract an archive:\n\n      tar xf source.tar\n\n",
+        );
+
+    testenv
+        .command()
+        .args(["tar", "--example", "3"])
+        .assert()
+        .failure()
+        .stdout(is_empty())
+        .stderr(contains("Example 3 not found, the page has 2 examples"));
+
+    testenv
+        .command()
+        .args(["tar", "--code-only"])
+        .assert()
+        .success()
+        .stdout("tar cf {{target.tar}} {{file1}}\ntar xf {{source.tar}}\n");
+
+    testenv
+        .command()
+        .args(["--color", "always", "tar", "--code-only", "--example", "1"])
+        .assert()
+        .success()
+        .stdout("tar cf {{target.tar}} {{file1}}\n");
+}
+
+#[test]
+fn test_render_malformed_page() {
+    let testenv = TestEnv::new();
+    let file_path = testenv.input_dir.path().join("malformed.md");
+    std::fs::write(
+        &file_path,
+        "# tar\n\n> Archiving utility.\n\n- Extract an archive:\n",
+    )
+    .unwrap();
+
+    testenv
+        .command()
+        .args(["-f", file_path.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stdout(is_empty())
+        .stderr(contains(format!(
+            "Could not parse the page at {}",
+            file_path.display()
+        )))
+        .stderr(contains("The example `Extract an archive:` has no code"));
+
+    // The raw markdown can still be shown
+    testenv
+        .command()
+        .args(["--raw", "-f", file_path.to_str().unwrap()])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_fill_example() {
+    let testenv = TestEnv::new();
+    testenv.add_entry(
+        "tar",
+        "# tar\n\n> Archiving utility.\n\n\
+         - Create an archive from files:\n\n`tar cf {{target.tar}} {{file1}}`\n\n\
+         - Extract an archive:\n\n`tar xf {{source.tar}} -C {{directory}}`\n",
+    );
+
+    assert_cmd::Command::from_std(testenv.command())
+        .args(["--color", "never", "tar", "--fill"])
+        .write_stdin("2\nbackup.tar\n\n")
+        .assert()
+        .success()
+        .stdout("tar xf backup.tar -C directory\n")
+        .stderr(contains("2. Extract an archive:"))
+        .stderr(contains("source.tar: "));
+
+    assert_cmd::Command::from_std(testenv.command())
+        .args(["tar", "--fill", "--example", "1"])
+        .write_stdin("backup.tar\na.txt\n")
+        .assert()
+        .success()
+        .stdout("tar cf backup.tar a.txt\n");
+
+    assert_cmd::Command::from_std(testenv.command())
+        .args(["tar", "--fill", "--example", "1"])
+        .write_stdin("backup.tar\n")
+        .assert()
+        .failure()
+        .stdout(is_empty())
+        .stderr(contains("Aborted, no input was given"));
+}
+
+#[test]
+fn test_max_width() {
+    let testenv = TestEnv::new();
+    testenv.write_config("[display]\nmax_width = 30\n");
+    testenv.add_entry(
+        "tar",
+        "# tar\n\n> Archiving utility, often combined with a compression method.\n\n\
+         - Create an archive from files:\n\n\
+         `tar cf {{path/to/target.tar}} {{path/to/file1 path/to/file2}}`\n",
+    );
+
+    testenv
+        .command()
+        .args(["--color", "never", "tar"])
+        .assert()
+        .success()
+        .stdout(
+            "\n  Archiving utility, often\n  combined with a compression\n  method.\n\n\
+             \x20 Create an archive from\n  files:\n\n\
+             \x20     tar cf \\\n\
+             \x20       path/to/target.tar \\\n\
+             \x20       path/to/file1 path/to/file2\n\n",
+        );
+
+    // Wrapped text lines don't end with empty escape sequences
+    testenv
+        .command()
+        .args(["--color", "always", "tar"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "  Archiving utility, often\n  combined with a compression\n",
+        ))
+        .stdout(contains("\x1b[0m\n  \x1b[32mfiles:"));
+}
+
+#[test]
+fn test_hyperlinks() {
+    let testenv = TestEnv::new();
+    testenv.add_entry(
+        "tar",
+        "# tar\n\n> Archiving utility.\n\
+         > More information: <https://www.gnu.org/software/tar>.\n\n\
+         - Extract an archive:\n\n`tar xf {{source.tar}}`\n",
+    );
+
+    testenv
+        .command()
+        .args(["--color", "always", "tar"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "\x1b]8;;https://www.gnu.org/software/tar\x1b\\\
+             \x1b[4mhttps://www.gnu.org/software/tar\x1b[0m\
+             \x1b]8;;\x1b\\",
+        ));
+
+    // Without styles, the URL is printed as it is
+    testenv
+        .command()
+        .args(["--color", "never", "tar"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "More information: <https://www.gnu.org/software/tar>.\n",
+        ))
+        .stdout(contains("\x1b").not());
+}
+
+#[test]
+fn test_inline_markdown() {
+    let testenv = TestEnv::new();
+    testenv.add_entry(
+        "tar",
+        "# tar\n\n> Archiving utility, **not** a compression tool.\n\n\
+         - Create a `tar` archive:\n\n`tar cf {{target.tar}} {{file}}`\n",
+    );
+
+    testenv
+        .command()
+        .args(["--color", "always", "tar"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Archiving utility, \x1b[1mnot\x1b[0m a compression",
+        ))
+        .stdout(contains(
+            "Create a \x1b[0m\x1b[36mtar\x1b[0m\x1b[32m archive:",
+        ))
+        .stdout(contains("`").not());
+
+    // Without styles, the markdown is printed as it is
+    testenv
+        .command()
+        .args(["--color", "never", "tar"])
+        .assert()
+        .success()
+        .stdout(contains("Archiving utility, **not** a compression tool."))
+        .stdout(contains("Create a `tar` archive:"));
+}
+
+#[test]
+fn test_option_placeholders() {
+    let testenv = TestEnv::new();
+    testenv.add_entry(
+        "tar",
+        "# tar\n\n> Archiving utility.\n\n\
+         - Extract an archive:\n\n`tar {{[-x|--extract]}} {{[-f|--file]}} {{source.tar}}`\n",
+    );
+
+    testenv
+        .command()
+        .args(["--color", "never", "tar"])
+        .assert()
+        .success()
+        .stdout(contains("      tar --extract --file source.tar\n"));
+
+    testenv.write_config("[display]\noption_style = \"short\"\n");
+    testenv
+        .command()
+        .args(["--color", "always", "tar"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "\x1b[36m \x1b[0m\x1b[36m-x\x1b[0m\x1b[36m \x1b[0m\x1b[36m-f\x1b[0m",
+        ));
+    testenv
+        .command()
+        .args(["tar", "--code-only"])
+        .assert()
+        .success()
+        .stdout("tar -x -f {{source.tar}}\n");
+
+    testenv.write_config("[display]\noption_style = \"both\"\n");
+    testenv
+        .command()
+        .args(["--color", "never", "tar"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "      tar [-x|--extract] [-f|--file] source.tar\n",
+        ));
+    testenv
+        .command()
+        .args(["tar", "--code-only"])
+        .assert()
+        .success()
+        .stdout("tar [-x|--extract] [-f|--file] {{source.tar}}\n");
+}
+
+#[test]
+fn test_escaped_braces() {
+    let testenv = TestEnv::new();
+    testenv.add_entry(
+        "docker-inspect",
+        "# docker inspect\n\n> Show information about containers.\n\n\
+         - Show the ID of a container:\n\n\
+         `docker inspect --format '\\{\\{.Id\\}\\}' {{container}}`\n",
+    );
+
+    testenv
+        .command()
+        .args(["--color", "never", "docker-inspect"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "      docker inspect --format '{{.Id}}' container\n",
+        ));
+    testenv
+        .command()
+        .args(["docker-inspect", "--code-only"])
+        .assert()
+        .success()
+        .stdout("docker inspect --format '{{.Id}}' {{container}}\n");
+}